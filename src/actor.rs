@@ -1,2 +1,33 @@
+mod exchange_rate;
+mod feeder_cost;
+mod governance;
 mod oracle;
-pub use oracle::OracleActor;
+mod oracle_reward;
+mod proposer;
+mod reference_price;
+mod reward;
+mod staking;
+mod swap;
+mod tx_failure;
+mod validator;
+mod wasm;
+mod watchlist;
+mod whale;
+pub use exchange_rate::ExchangeRateActor;
+pub use feeder_cost::{CostWindow, FeederCostActor};
+pub use governance::{GovernanceActor, ProposalState};
+pub use oracle::{
+    DenomThreshold, DriftStreak, OracleActor, OracleParams, OraclePeriodRecord, OracleState,
+    OracleVoteOutcome,
+};
+pub use oracle_reward::{OracleRewardActor, OracleRewardTally};
+pub use proposer::ProposerActor;
+pub use reference_price::{ReferencePriceActor, ReferencePriceMapping, ReferencePriceSource};
+pub use reward::{RewardActor, RewardWindow};
+pub use staking::{StakingActor, BOND_DENOM};
+pub use swap::{SwapActor, DEFAULT_SWAP_PERIOD};
+pub use tx_failure::{classify_failure, TxFailureActor};
+pub use validator::ValidatorActor;
+pub use wasm::WasmActor;
+pub use watchlist::{WatchedAddress, WatchlistActor};
+pub use whale::{WhaleActor, WhaleThreshold};
@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+use crate::messages::{
+    ExchangeRateCandle, ExchangeRatePoint, GetExchangeRateAt, GetExchangeRateCandles,
+    GetExchangeRateRange, MessageBlockEventExchangeRate, MessageExchangeRateCandle,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// keeps the on-chain oracle exchange rates, and builds OHLC candles from them
+pub struct ExchangeRateActor {
+    /// candle length
+    pub interval: Duration,
+    /// rates & candles older than this are dropped. None keeps everything
+    pub retention: Option<Duration>,
+    /// rates by denom, then height
+    pub rates: HashMap<String, BTreeMap<u64, ExchangeRatePoint>>,
+    /// candles by denom, then start time
+    pub candles: HashMap<String, BTreeMap<DateTime<Utc>, ExchangeRateCandle>>,
+}
+impl ExchangeRateActor {
+    /// candles are whole seconds, so `interval` must be at least a second
+    pub fn create(
+        interval: Duration,
+        retention: Option<Duration>,
+    ) -> anyhow::Result<ExchangeRateActor> {
+        if interval.num_seconds() < 1 {
+            return Err(anyhow::anyhow!(
+                "candle interval must be at least a second, not {}",
+                interval
+            ));
+        }
+        Ok(ExchangeRateActor {
+            interval,
+            retention,
+            rates: Default::default(),
+            candles: Default::default(),
+        })
+    }
+
+    /// start of the candle which `time` falls in
+    fn candle_start(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = self.interval.num_seconds();
+        let timestamp = time.timestamp();
+        Utc.timestamp_opt(timestamp - timestamp.rem_euclid(interval), 0)
+            .single()
+    }
+
+    fn add_to_candle(&mut self, denom: &str, point: &ExchangeRatePoint) {
+        let start = match self.candle_start(point.time) {
+            Some(start) => start,
+            None => {
+                log::error!(
+                    "No candle start for {} {} at {}",
+                    denom,
+                    point.height,
+                    point.time
+                );
+                return;
+            }
+        };
+        let end = start + self.interval;
+        let candles = self
+            .candles
+            .entry(denom.into())
+            .or_insert_with(BTreeMap::new);
+        if let Some(candle) = candles.get_mut(&start) {
+            if point.exchange_rate > candle.high {
+                candle.high = point.exchange_rate
+            }
+            if point.exchange_rate < candle.low {
+                candle.low = point.exchange_rate
+            }
+            candle.close = point.exchange_rate;
+            candle.last_height = point.height;
+            return;
+        }
+        // a new candle, so the previous one has closed
+        if let Some(previous) = candles.get(&(start - self.interval)) {
+            Broker::<SystemBroker>::issue_async(MessageExchangeRateCandle {
+                candle: previous.clone(),
+            });
+        }
+        candles.insert(
+            start,
+            ExchangeRateCandle {
+                denom: denom.into(),
+                start,
+                end,
+                open: point.exchange_rate,
+                high: point.exchange_rate,
+                low: point.exchange_rate,
+                close: point.exchange_rate,
+                first_height: point.height,
+                last_height: point.height,
+            },
+        );
+    }
+
+    fn prune(&mut self, denom: &str, now: DateTime<Utc>) {
+        if let Some(retention) = self.retention {
+            let cutoff = now - retention;
+            if let Some(rates) = self.rates.get_mut(denom) {
+                rates.retain(|_, point| point.time >= cutoff);
+            }
+            if let Some(candles) = self.candles.get_mut(denom) {
+                candles.retain(|_, candle| candle.end >= cutoff);
+            }
+        }
+    }
+
+    /// the rate in effect at `height`
+    pub fn rate_at(&self, denom: &str, height: u64) -> Option<ExchangeRatePoint> {
+        self.rates
+            .get(denom)?
+            .range(..=height)
+            .next_back()
+            .map(|(_, point)| point.clone())
+    }
+}
+impl Actor for ExchangeRateActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockEventExchangeRate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for ExchangeRateActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Exchange Rate Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockEventExchangeRate> for ExchangeRateActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventExchangeRate, _ctx: &mut Self::Context) {
+        let point = ExchangeRatePoint {
+            height: msg.height,
+            time: msg.time,
+            exchange_rate: msg.exchange_rate,
+        };
+        self.add_to_candle(&msg.denom, &point);
+        self.rates
+            .entry(msg.denom.clone())
+            .or_insert_with(BTreeMap::new)
+            .insert(msg.height, point);
+        self.prune(&msg.denom, msg.time);
+    }
+}
+
+impl Handler<GetExchangeRateAt> for ExchangeRateActor {
+    type Result = MessageResult<GetExchangeRateAt>;
+
+    fn handle(&mut self, msg: GetExchangeRateAt, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.rate_at(&msg.denom, msg.height))
+    }
+}
+
+impl Handler<GetExchangeRateRange> for ExchangeRateActor {
+    type Result = MessageResult<GetExchangeRateRange>;
+
+    fn handle(&mut self, msg: GetExchangeRateRange, _ctx: &mut Self::Context) -> Self::Result {
+        match self.rates.get(&msg.denom) {
+            Some(rates) if msg.from_height <= msg.to_height => MessageResult(
+                rates
+                    .range(msg.from_height..=msg.to_height)
+                    .map(|(_, point)| point.clone())
+                    .collect(),
+            ),
+            _ => MessageResult(vec![]),
+        }
+    }
+}
+
+impl Handler<GetExchangeRateCandles> for ExchangeRateActor {
+    type Result = MessageResult<GetExchangeRateCandles>;
+
+    fn handle(&mut self, msg: GetExchangeRateCandles, _ctx: &mut Self::Context) -> Self::Result {
+        match self.candles.get(&msg.denom) {
+            Some(candles) if msg.from <= msg.to => MessageResult(
+                candles
+                    .range(msg.from..=msg.to)
+                    .map(|(_, candle)| candle.clone())
+                    .collect(),
+            ),
+            _ => MessageResult(vec![]),
+        }
+    }
+}
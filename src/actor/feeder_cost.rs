@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::messages::{
+    FeederCost, MessageBlockProposed, MessageFeederCostSummary, MessageFeederVoteFailed, MessageTX,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// when a feeder cost summary is published
+#[derive(Clone, Debug)]
+pub enum CostWindow {
+    /// every N blocks
+    Blocks(u64),
+    /// when the (UTC) date of the blocks changes
+    Day,
+}
+
+/// aggregates the gas & fees spent by each validator's oracle feeder
+pub struct FeederCostActor {
+    pub window: CostWindow,
+    pub window_start_height: u64,
+    pub window_start_date: Option<NaiveDate>,
+    pub last_height: u64,
+    pub costs: HashMap<String, FeederCost>,
+}
+impl FeederCostActor {
+    pub fn create(window: CostWindow) -> FeederCostActor {
+        FeederCostActor {
+            window,
+            window_start_height: 0,
+            window_start_date: None,
+            last_height: 0,
+            costs: Default::default(),
+        }
+    }
+
+    fn window_closed(&self, height: u64, date: NaiveDate) -> bool {
+        match self.window {
+            CostWindow::Blocks(blocks) => height >= self.window_start_height + blocks,
+            CostWindow::Day => self.window_start_date.map(|d| d != date).unwrap_or(false),
+        }
+    }
+
+    fn publish(&mut self, height: u64, date: NaiveDate) {
+        if !self.costs.is_empty() {
+            let mut costs = self.costs.drain().map(|c| c.1).collect::<Vec<_>>();
+            costs.sort_by(|a, b| a.operator_address.cmp(&b.operator_address));
+            log::info!(
+                "Feeder costs {}-{} for {} validators",
+                self.window_start_height,
+                self.last_height,
+                costs.len()
+            );
+            Broker::<SystemBroker>::issue_async(MessageFeederCostSummary {
+                from_height: self.window_start_height,
+                to_height: self.last_height,
+                costs,
+            });
+        }
+        self.window_start_height = height;
+        self.window_start_date = Some(date);
+    }
+}
+impl Actor for FeederCostActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for FeederCostActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Feeder Cost Actor Stopping");
+        ctx.stop()
+    }
+}
+
+/// the window is closed by the first block past it, so it is published even when no votes follow
+impl Handler<MessageBlockProposed> for FeederCostActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        let height = msg.height;
+        let date = msg.time.naive_utc().date();
+        if self.window_start_date.is_none() {
+            self.window_start_height = height;
+            self.window_start_date = Some(date);
+        } else if self.window_closed(height, date) {
+            self.publish(height, date);
+        }
+        self.last_height = height;
+    }
+}
+
+impl Handler<MessageTX> for FeederCostActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        let tx = msg.tx;
+        let height = tx.height;
+
+        // a feeder usually bundles a prevote & vote for a single validator
+        let mut voters: Vec<(String, String)> = vec![];
+        for m in &tx.tx.body.messages {
+            match m.get("@type").and_then(Value::as_str) {
+                Some("/terra.oracle.v1beta1.MsgAggregateExchangeRateVote")
+                | Some("/terra.oracle.v1beta1.MsgAggregateExchangeRatePrevote") => {
+                    let validator = m.get("validator").and_then(Value::as_str);
+                    let feeder = m.get("feeder").and_then(Value::as_str);
+                    if let (Some(validator), Some(feeder)) = (validator, feeder) {
+                        let voter = (validator.to_string(), feeder.to_string());
+                        if !voters.contains(&voter) {
+                            voters.push(voter);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if voters.is_empty() {
+            return;
+        }
+
+        let failed = tx.is_failed();
+        let fee = tx.fee();
+        let share = voters.len() as u64;
+        for (i, (operator_address, feeder)) in voters.into_iter().enumerate() {
+            // the first voters take the remainder, so shared txs are counted in full
+            let i = i as u64;
+            let gas_wanted = tx.gas_wanted / share + u64::from(i < tx.gas_wanted % share);
+            let gas_used = tx.gas_used / share + u64::from(i < tx.gas_used % share);
+            if failed {
+                log::warn!(
+                    "Vote failed {} {} {} {}",
+                    height,
+                    operator_address,
+                    tx.txhash,
+                    tx.raw_log
+                );
+                Broker::<SystemBroker>::issue_async(MessageFeederVoteFailed {
+                    height,
+                    operator_address: operator_address.clone(),
+                    feeder: feeder.clone(),
+                    txhash: tx.txhash.clone(),
+                    raw_log: tx.raw_log.clone(),
+                });
+            }
+            let cost = self
+                .costs
+                .entry(operator_address.clone())
+                .or_insert_with(|| FeederCost {
+                    operator_address,
+                    feeder: feeder.clone(),
+                    txs: 0,
+                    failed_txs: 0,
+                    gas_wanted: 0,
+                    gas_used: 0,
+                    fees: Default::default(),
+                });
+            cost.feeder = feeder;
+            cost.txs += 1;
+            if failed {
+                cost.failed_txs += 1;
+            }
+            cost.gas_wanted += gas_wanted;
+            cost.gas_used += gas_used;
+            for coin in fee.iter() {
+                // like gas, the first voters take a unit each of the remainder
+                let base = (coin.amount / Decimal::from(share)).trunc();
+                let remainder = coin.amount - base * Decimal::from(share);
+                let fee_share = base
+                    + (remainder - Decimal::from(i))
+                        .max(Decimal::ZERO)
+                        .min(Decimal::ONE);
+                *cost.fees.entry(coin.denom.clone()).or_insert(Decimal::ZERO) += fee_share;
+            }
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::errors::LcdError;
+use crate::lcd::LcdClient;
+use crate::messages::{
+    MessageBlockProposed, MessageGovDeposit, MessageGovProposalEnded, MessageGovProposalSubmitted,
+    MessageGovVote, MessageGovVotingStarted, MessageTX, MessageValidatorEvent, ValidatorEventType,
+};
+use crate::types::{MsgDeposit, MsgSubmitProposal, MsgVote, MsgVoteWeighted};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// how often the proposals in their voting period are re-read from the LCD
+pub const DEFAULT_PROPOSAL_REFRESH: Duration = Duration::from_secs(600);
+/// how often voting deadlines are checked
+pub const DEFAULT_REMINDER_CHECK: Duration = Duration::from_secs(60);
+
+/// a proposal in its deposit or voting period
+#[derive(Clone, Debug, Default)]
+pub struct ProposalState {
+    pub proposal_id: u64,
+    pub title: Option<String>,
+    pub voting: bool,
+    pub voting_end_time: Option<DateTime<Utc>>,
+    /// deposits seen since we started, by denom
+    pub deposits: HashMap<String, Decimal>,
+    /// vote option, by voter
+    pub votes: HashMap<String, String>,
+    /// accounts already reminded to vote
+    pub reminded: HashSet<String>,
+}
+
+/// tracks governance proposals & votes, reminding us when our validators have not voted
+/// on a proposal close to the end of its voting period
+pub struct GovernanceActor {
+    pub lcd: LcdClient,
+    /// our validators' account (voting) addresses, with their operator address
+    pub accounts: HashMap<String, String>,
+    /// how long before the end of voting a missing vote is raised
+    pub remind_before: chrono::Duration,
+    pub refresh: Duration,
+    pub proposals: BTreeMap<u64, ProposalState>,
+    pub last_height: u64,
+}
+impl GovernanceActor {
+    pub fn create(
+        lcd: &str,
+        accounts: HashMap<String, String>,
+        remind_before: chrono::Duration,
+    ) -> GovernanceActor {
+        GovernanceActor {
+            lcd: LcdClient::create(lcd),
+            accounts,
+            remind_before,
+            refresh: DEFAULT_PROPOSAL_REFRESH,
+            proposals: Default::default(),
+            last_height: 0,
+        }
+    }
+
+    fn proposal(&mut self, proposal_id: u64) -> &mut ProposalState {
+        self.proposals
+            .entry(proposal_id)
+            .or_insert_with(|| ProposalState {
+                proposal_id,
+                ..Default::default()
+            })
+    }
+
+    /// read the proposals in their voting period, and how our accounts voted on them
+    fn refresh_proposals(&self, ctx: &mut Context<Self>) {
+        let lcd = self.lcd.clone();
+        let accounts: Vec<String> = self.accounts.keys().cloned().collect();
+        let fut = async move {
+            let mut proposals = vec![];
+            for proposal in lcd.voting_proposals().await? {
+                let mut votes = vec![];
+                if let Some(proposal_id) = proposal.get("proposal_id").and_then(Value::as_str) {
+                    for account in accounts.iter() {
+                        // one failed query leaves that vote as it was
+                        match lcd.vote(proposal_id, account).await {
+                            Ok(Some(option)) => votes.push((account.clone(), option)),
+                            Ok(None) => {}
+                            Err(e) => log::error!(
+                                "Unable to fetch vote of {} on proposal {}: {}",
+                                account,
+                                proposal_id,
+                                e
+                            ),
+                        }
+                    }
+                }
+                proposals.push((proposal, votes));
+            }
+            Ok::<_, LcdError>(proposals)
+        };
+        ctx.spawn(fut.into_actor(self).map(|result, act, _ctx| match result {
+            Ok(proposals) => {
+                for (proposal, votes) in proposals {
+                    act.apply_proposal(&proposal, votes);
+                }
+            }
+            Err(e) => log::error!("Unable to fetch proposals from {}: {}", act.lcd.url, e),
+        }));
+    }
+
+    fn apply_proposal(&mut self, proposal: &Value, votes: Vec<(String, String)>) {
+        let proposal_id = match proposal
+            .get("proposal_id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.parse().ok())
+        {
+            Some(proposal_id) => proposal_id,
+            None => return,
+        };
+        let state = self.proposal(proposal_id);
+        state.voting = true;
+        if let Some(title) = proposal.pointer("/content/title").and_then(Value::as_str) {
+            state.title = Some(title.into());
+        }
+        state.voting_end_time = proposal
+            .get("voting_end_time")
+            .and_then(Value::as_str)
+            .and_then(|end| end.parse::<DateTime<Utc>>().ok());
+        for (voter, option) in votes {
+            state.votes.insert(voter, option);
+        }
+    }
+
+    /// raise a warning for each of our accounts yet to vote on a proposal ending soon
+    fn check_reminders(&mut self) {
+        let now = Utc::now();
+        for proposal in self.proposals.values_mut().filter(|p| p.voting) {
+            let voting_end_time = match proposal.voting_end_time {
+                Some(end) if end > now && end - self.remind_before <= now => end,
+                _ => continue,
+            };
+            for (account, operator_address) in self.accounts.iter() {
+                if proposal.votes.contains_key(account)
+                    || !proposal.reminded.insert(account.clone())
+                {
+                    continue;
+                }
+                let message = format!(
+                    "Proposal {} '{}' voting ends {} and {} has not voted",
+                    proposal.proposal_id,
+                    proposal.title.clone().unwrap_or_default(),
+                    voting_end_time,
+                    account
+                );
+                log::warn!("{}", message);
+                Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+                    height: self.last_height,
+                    operator_address: operator_address.clone(),
+                    moniker: None,
+                    event_type: ValidatorEventType::WARN,
+                    message,
+                    hash: None,
+                });
+            }
+        }
+    }
+}
+impl Actor for GovernanceActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageGovProposalEnded>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        self.refresh_proposals(ctx);
+        ctx.run_interval(self.refresh, |act, ctx| act.refresh_proposals(ctx));
+        ctx.run_interval(DEFAULT_REMINDER_CHECK, |act, _ctx| act.check_reminders());
+    }
+}
+
+impl Handler<MessageStop> for GovernanceActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Governance Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockProposed> for GovernanceActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        self.last_height = msg.height;
+    }
+}
+
+impl Handler<MessageGovProposalEnded> for GovernanceActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageGovProposalEnded, _ctx: &mut Self::Context) {
+        if let Some(proposal) = self.proposals.remove(&msg.proposal_id) {
+            log::info!(
+                "Proposal {} '{}' ended: {}",
+                msg.proposal_id,
+                proposal.title.unwrap_or_default(),
+                msg.result
+            );
+        }
+    }
+}
+
+impl Handler<MessageTX> for GovernanceActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, ctx: &mut Self::Context) {
+        if msg.tx.is_failed() {
+            return;
+        }
+        let height = msg.tx.height;
+        let txhash = msg.tx.txhash.clone();
+        let events = msg.tx.log_events();
+        for (msg_index, m) in msg.tx.tx.body.messages.iter().enumerate() {
+            match m.get("@type").and_then(Value::as_str) {
+                Some("/cosmos.gov.v1beta1.MsgSubmitProposal") => {
+                    match serde_json::from_value::<MsgSubmitProposal>(m.clone()) {
+                        Ok(submit) => {
+                            let proposal_id_o = events
+                                .iter()
+                                .filter(|e| {
+                                    e.msg_index == msg_index && e.s_type == "submit_proposal"
+                                })
+                                .find_map(|e| e.attribute("proposal_id"))
+                                .and_then(|id| id.parse::<u64>().ok());
+                            let proposal_id = match proposal_id_o {
+                                Some(proposal_id) => proposal_id,
+                                None => {
+                                    log::warn!("Proposal without id: {} {}", height, txhash);
+                                    continue;
+                                }
+                            };
+                            let title = submit
+                                .content
+                                .get("title")
+                                .and_then(Value::as_str)
+                                .map(String::from);
+                            log::info!(
+                                "Proposal submitted: {} {} {}",
+                                height,
+                                proposal_id,
+                                title.clone().unwrap_or_default()
+                            );
+                            let proposal = self.proposal(proposal_id);
+                            proposal.title = title.clone();
+                            for coin in submit.initial_deposit.iter() {
+                                *proposal
+                                    .deposits
+                                    .entry(coin.denom.clone())
+                                    .or_insert(Decimal::ZERO) += coin.amount;
+                            }
+                            Broker::<SystemBroker>::issue_async(MessageGovProposalSubmitted {
+                                height,
+                                txhash: txhash.clone(),
+                                proposal_id,
+                                proposer: submit.proposer,
+                                title,
+                                content_type: submit
+                                    .content
+                                    .get("@type")
+                                    .and_then(Value::as_str)
+                                    .map(String::from),
+                                initial_deposit: submit.initial_deposit,
+                            });
+                        }
+                        Err(e) => {
+                            log::error!("Expected submit proposal: {} - {}", e, m.to_string())
+                        }
+                    }
+                }
+                Some("/cosmos.gov.v1beta1.MsgDeposit") => {
+                    match serde_json::from_value::<MsgDeposit>(m.clone()) {
+                        Ok(deposit) => {
+                            let proposal = self.proposal(deposit.proposal_id);
+                            for coin in deposit.amount.iter() {
+                                *proposal
+                                    .deposits
+                                    .entry(coin.denom.clone())
+                                    .or_insert(Decimal::ZERO) += coin.amount;
+                            }
+                            Broker::<SystemBroker>::issue_async(MessageGovDeposit {
+                                height,
+                                txhash: txhash.clone(),
+                                proposal_id: deposit.proposal_id,
+                                depositor: deposit.depositor,
+                                amount: deposit.amount,
+                            });
+                        }
+                        Err(e) => log::error!("Expected deposit: {} - {}", e, m.to_string()),
+                    }
+                }
+                Some("/cosmos.gov.v1beta1.MsgVote") => {
+                    match serde_json::from_value::<MsgVote>(m.clone()) {
+                        Ok(vote) => {
+                            self.proposal(vote.proposal_id)
+                                .votes
+                                .insert(vote.voter.clone(), vote.option.clone());
+                            Broker::<SystemBroker>::issue_async(MessageGovVote {
+                                height,
+                                txhash: txhash.clone(),
+                                proposal_id: vote.proposal_id,
+                                voter: vote.voter,
+                                option: vote.option,
+                            });
+                        }
+                        Err(e) => log::error!("Expected vote: {} - {}", e, m.to_string()),
+                    }
+                }
+                Some("/cosmos.gov.v1beta1.MsgVoteWeighted") => {
+                    match serde_json::from_value::<MsgVoteWeighted>(m.clone()) {
+                        Ok(vote) => {
+                            let option = vote
+                                .options
+                                .iter()
+                                .map(|o| format!("{}:{}", o.option, o.weight))
+                                .collect::<Vec<_>>()
+                                .join(",");
+                            self.proposal(vote.proposal_id)
+                                .votes
+                                .insert(vote.voter.clone(), option.clone());
+                            Broker::<SystemBroker>::issue_async(MessageGovVote {
+                                height,
+                                txhash: txhash.clone(),
+                                proposal_id: vote.proposal_id,
+                                voter: vote.voter,
+                                option,
+                            });
+                        }
+                        Err(e) => log::error!("Expected weighted vote: {} - {}", e, m.to_string()),
+                    }
+                }
+                _ => {}
+            }
+        }
+        // the deposit which reaches the minimum starts the voting period
+        let mut voting_started = false;
+        for event in events.iter() {
+            if event.s_type != "submit_proposal" && event.s_type != "proposal_deposit" {
+                continue;
+            }
+            if let Some(proposal_id) = event
+                .attribute("voting_period_start")
+                .and_then(|id| id.parse::<u64>().ok())
+            {
+                log::info!("Proposal voting started: {} {}", height, proposal_id);
+                self.proposal(proposal_id).voting = true;
+                voting_started = true;
+                Broker::<SystemBroker>::issue_async(MessageGovVotingStarted {
+                    height,
+                    txhash: txhash.clone(),
+                    proposal_id,
+                });
+            }
+        }
+        if voting_started {
+            // pick up the end of the voting period
+            self.refresh_proposals(ctx);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::{Div, Mul};
 
 use actix::prelude::*;
@@ -6,16 +6,214 @@ use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
 use rust_decimal::prelude::*;
 //use rust_decimal_macros::dec;
 use terra_rust_api::core_types::Coin;
-use terra_rust_api::messages::oracle::MsgAggregateExchangeRateVote;
+use terra_rust_api::messages::oracle::{MsgAggregateExchangeRateVote, MsgDelegateFeedConsent};
 use terra_rust_api::Terra;
 
+/// outcomes are part of the published vote period, so live with the messages
+pub use crate::messages::OracleVoteOutcome;
 use crate::messages::{
-    MessagePriceAbstain, MessagePriceDrift, MessageTX, MessageValidatorEvent,
-    MessageValidatorStakedTotal, ValidatorEventType,
+    DriftSeverity, GetFeederValidator, GetOraclePerformance, GetValidatorFeeder, GetVoteTiming,
+    MessageFeederDelegated, MessageFeederMismatch, MessageLateVotes, MessageOracleMissedVote,
+    MessageOracleParameterChanged, MessageOracleVotePeriod, MessagePriceAbstain, MessagePriceDrift,
+    MessagePriceDriftOngoing, MessagePriceDriftRecovered, MessageTX, MessageValidator,
+    MessageValidatorEvent, MessageValidatorRemoved, MessageValidatorStakedTotal,
+    MessageVoteWhitelistMismatch, OraclePerformance, OraclePeriodVote, ValidatorEventType,
+    ValidatorFeeder, VoteTiming,
 };
 use crate::BrokerType;
 use constellation_shared::MessageStop;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// how often the oracle parameters & whitelist are re-read from the LCD
+pub const DEFAULT_PARAMETER_REFRESH: Duration = Duration::from_secs(600);
+
+/// the chain's oracle module parameters
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OracleParams {
+    pub vote_period: u64,
+    pub vote_threshold: Decimal,
+    pub reward_band: Decimal,
+    pub reward_distribution_window: u64,
+    pub slash_fraction: Decimal,
+    pub slash_window: u64,
+    pub min_valid_per_window: Decimal,
+    /// whitelisted denoms
+    #[serde(default)]
+    pub whitelist: Vec<String>,
+}
+impl OracleParams {
+    /// load parameters from a snapshot (see [`OracleParams::save`]).
+    /// files ending in .toml are read as TOML, anything else as JSON
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<OracleParams> {
+        read_snapshot(path.as_ref())
+    }
+
+    /// save parameters as a snapshot, so the actor can later be run offline.
+    /// files ending in .toml are written as TOML, anything else as JSON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        if is_toml(path.as_ref()) {
+            write_toml(path.as_ref(), self)
+        } else {
+            let writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(writer, self)?;
+            Ok(())
+        }
+    }
+
+    pub async fn fetch(lcd: &str, chain: &str) -> anyhow::Result<OracleParams> {
+        let terra = Terra::lcd_client_no_tx(lcd, chain).await?;
+        let params = terra.oracle().parameters().await?.result;
+        let mut whitelist = params
+            .whitelist
+            .iter()
+            .map(|w| w.name.clone())
+            .collect::<Vec<String>>();
+        whitelist.sort();
+
+        Ok(OracleParams {
+            vote_period: params.vote_period,
+            vote_threshold: Decimal::from_f64(params.vote_threshold).unwrap(),
+            reward_band: Decimal::from_f64(params.reward_band).unwrap(),
+            reward_distribution_window: params.reward_distribution_window,
+            slash_fraction: Decimal::from_f64(params.slash_fraction).unwrap(),
+            slash_window: params.slash_window,
+            min_valid_per_window: Decimal::from_f64(params.min_valid_per_window).unwrap(),
+            whitelist,
+        })
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("toml"))
+        .unwrap_or(false)
+}
+
+/// read a snapshot, as TOML when the file ends in .toml, anything else as JSON
+fn read_snapshot<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    if is_toml(path) {
+        read_toml(path)
+    } else {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+#[cfg(feature = "toml-snapshots")]
+fn read_toml<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[cfg(not(feature = "toml-snapshots"))]
+fn read_toml<T: DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+    Err(anyhow::anyhow!(
+        "{} is TOML; build with the toml-snapshots feature to read it",
+        path.display()
+    ))
+}
+
+#[cfg(feature = "toml-snapshots")]
+fn write_toml<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    std::fs::write(path, toml::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "toml-snapshots"))]
+fn write_toml<T: Serialize>(path: &Path, _value: &T) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "{} is TOML; build with the toml-snapshots feature to write it",
+        path.display()
+    ))
+}
+
+/// alerting thresholds for a denom. bands are fractions of the median
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DenomThreshold {
+    /// rates at or below this are treated as an abstain
+    pub abstain_floor: Decimal,
+    pub warning_band: Decimal,
+    /// drifts beyond this are critical. when unset, drifts are only ever warnings
+    #[serde(default)]
+    pub critical_band: Option<Decimal>,
+}
+impl DenomThreshold {
+    /// load per-denom thresholds (a map of denom to threshold).
+    /// files ending in .toml are read as TOML, anything else as JSON
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<HashMap<String, DenomThreshold>> {
+        read_snapshot(path.as_ref())
+    }
+}
+
+/// a validator drifting on a denom for consecutive vote periods
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DriftStreak {
+    pub start_height: u64,
+    pub periods: u64,
+    pub worst_severity: DriftSeverity,
+    pub max_band_multiple: Decimal,
+}
+/// number of vote periods between summaries of a continuing drift
+pub const DEFAULT_DRIFT_SUMMARY_PERIODS: u64 = 10;
+
+/// number of vote periods of history kept per validator
+pub const DEFAULT_HISTORY_PERIODS: usize = 100;
+
+/// a validator's vote for a single vote period
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OraclePeriodRecord {
+    pub height: u64,
+    pub rates: Vec<Coin>,
+    /// (submitted - median) / median, per denom
+    pub deviation: HashMap<String, Decimal>,
+    pub outcome: OracleVoteOutcome,
+}
+
+/// number of recent votes considered when checking for late voters
+pub const DEFAULT_LATE_VOTE_WINDOW: usize = 10;
+/// warn when this many of the recent votes landed in the last block of the period
+pub const DEFAULT_LATE_VOTE_COUNT: usize = 5;
+
+/// how often the actor's state is saved, when a state file is set
+pub const DEFAULT_STATE_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+/// a saved state older than this many blocks when the first block arrives is discarded
+pub const DEFAULT_MAX_STATE_AGE: u64 = 600;
+
+/// the parts of the actor's state which survive a restart
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OracleState {
+    pub height: u64,
+    pub validator_vote_last_seen: HashMap<String, u64>,
+    pub validator_missed_periods: HashMap<String, u64>,
+    pub validator_history: HashMap<String, VecDeque<OraclePeriodRecord>>,
+    pub feeder_delegation: HashMap<String, String>,
+    pub validator_feeder: HashMap<String, String>,
+    /// (operator, denom, streak)
+    pub drift_streaks: Vec<(String, String, DriftStreak)>,
+}
+impl OracleState {
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<OracleState> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// write to a temporary file first, so a crash mid-write leaves the previous state intact
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        {
+            let writer = BufWriter::new(File::create(&tmp)?);
+            serde_json::to_writer(writer, self)?;
+        }
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
 
 pub struct OracleActor {
     pub vote_period: u64,
@@ -25,30 +223,727 @@ pub struct OracleActor {
     pub slash_fraction: Decimal,
     pub slash_window: u64,
     pub min_valid_per_window: Decimal,
+    pub whitelist: Vec<String>,
+    /// LCD & chain used to refresh parameters. None when running offline
+    pub lcd: Option<(String, String)>,
+    pub parameter_refresh: Duration,
     pub last_avg_at_height: u64,
     pub validator_vote_last_seen: HashMap<String, u64>,
     pub validator_weight: HashMap<String, u64>,
     pub validator_vote_last_hash: HashMap<String, String>,
     pub validator_vote_prices: HashMap<String, Vec<Coin>>,
+    pub history_periods: usize,
+    pub validator_history: HashMap<String, VecDeque<OraclePeriodRecord>>,
+    /// feeder delegated via MsgDelegateFeedConsent, by operator
+    pub feeder_delegation: HashMap<String, String>,
+    /// feeder last seen submitting a vote, by operator
+    pub validator_feeder: HashMap<String, String>,
+    /// thresholds used for denoms without their own entry in `denom_thresholds`.
+    /// when None, the chain's reward band is used
+    pub default_threshold: Option<DenomThreshold>,
+    pub denom_thresholds: HashMap<String, DenomThreshold>,
+    /// current drift streaks, by (operator, denom)
+    pub drift_streaks: HashMap<(String, String), DriftStreak>,
+    pub drift_summary_periods: u64,
+    /// validators expected to vote, with their moniker
+    pub bonded_validators: HashMap<String, String>,
+    /// consecutive vote periods a validator has not voted in
+    pub validator_missed_periods: HashMap<String, u64>,
+    /// block offset within the vote period of recent votes, by operator
+    pub vote_offsets: HashMap<String, VecDeque<u64>>,
+    pub late_vote_window: usize,
+    pub late_vote_count: usize,
+    /// validators currently warned about voting late
+    pub late_voters: HashSet<String>,
+    pub last_height: u64,
+    /// where state is saved to & restored from
+    pub state_file: Option<PathBuf>,
+    pub state_save_interval: Duration,
+    pub max_state_age: u64,
+    /// height of restored state, until it has been checked against the first block seen
+    pub restored_height: Option<u64>,
 }
 impl OracleActor {
     pub async fn create(lcd: &str, chain: &str) -> anyhow::Result<OracleActor> {
+        let params = OracleParams::fetch(lcd, chain).await?;
+        let mut actor = OracleActor::from_params(params);
+        actor.lcd = Some((lcd.into(), chain.into()));
+
         let terra = Terra::lcd_client_no_tx(lcd, chain).await?;
-        let params = terra.oracle().parameters().await?.result;
+        for validator in crate::lcd::validators(&terra, "BOND_STATUS_BONDED").await? {
+            actor.bonded_validators.insert(
+                validator.operator_address.clone(),
+                validator.description.moniker.clone(),
+            );
+            actor
+                .validator_weight
+                .insert(validator.operator_address, validator.tokens);
+        }
+
+        Ok(actor)
+    }
 
-        Ok(OracleActor {
+    /// create an actor from known parameters, without contacting the LCD.
+    /// parameters will not be refreshed.
+    pub fn from_params(params: OracleParams) -> OracleActor {
+        OracleActor {
             vote_period: params.vote_period,
-            vote_threshold: Decimal::from_f64(params.vote_threshold).unwrap(),
-            reward_band: Decimal::from_f64(params.reward_band).unwrap(),
+            vote_threshold: params.vote_threshold,
+            reward_band: params.reward_band,
             reward_distribution_window: params.reward_distribution_window,
-            slash_fraction: Decimal::from_f64(params.slash_fraction).unwrap(),
+            slash_fraction: params.slash_fraction,
             slash_window: params.slash_window,
-            min_valid_per_window: Decimal::from_f64(params.min_valid_per_window).unwrap(),
+            min_valid_per_window: params.min_valid_per_window,
+            whitelist: params.whitelist,
+            lcd: None,
+            parameter_refresh: DEFAULT_PARAMETER_REFRESH,
             validator_vote_last_seen: Default::default(),
             validator_vote_prices: Default::default(),
             validator_vote_last_hash: Default::default(),
             validator_weight: Default::default(),
             last_avg_at_height: 0,
+            history_periods: DEFAULT_HISTORY_PERIODS,
+            validator_history: Default::default(),
+            feeder_delegation: Default::default(),
+            validator_feeder: Default::default(),
+            default_threshold: None,
+            denom_thresholds: Default::default(),
+            drift_streaks: Default::default(),
+            drift_summary_periods: DEFAULT_DRIFT_SUMMARY_PERIODS,
+            bonded_validators: Default::default(),
+            validator_missed_periods: Default::default(),
+            vote_offsets: Default::default(),
+            late_vote_window: DEFAULT_LATE_VOTE_WINDOW,
+            late_vote_count: DEFAULT_LATE_VOTE_COUNT,
+            late_voters: Default::default(),
+            last_height: 0,
+            state_file: None,
+            state_save_interval: DEFAULT_STATE_SAVE_INTERVAL,
+            max_state_age: DEFAULT_MAX_STATE_AGE,
+            restored_height: None,
+        }
+    }
+
+    /// periodically save state to `path`, restoring any state already there
+    pub fn with_state_file<P: AsRef<Path>>(mut self, path: P) -> OracleActor {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            match OracleState::load(&path) {
+                Ok(state) => {
+                    log::info!("Restored oracle state from height {}", state.height);
+                    self.restore(state);
+                }
+                Err(e) => log::error!("Unable to restore oracle state {:?}: {}", path, e),
+            }
+        }
+        self.state_file = Some(path);
+        self
+    }
+
+    pub fn state(&self) -> OracleState {
+        OracleState {
+            height: self.last_height,
+            validator_vote_last_seen: self.validator_vote_last_seen.clone(),
+            validator_missed_periods: self.validator_missed_periods.clone(),
+            validator_history: self.validator_history.clone(),
+            feeder_delegation: self.feeder_delegation.clone(),
+            validator_feeder: self.validator_feeder.clone(),
+            drift_streaks: self
+                .drift_streaks
+                .iter()
+                .map(|(key, streak)| (key.0.clone(), key.1.clone(), streak.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn restore(&mut self, state: OracleState) {
+        self.restored_height = Some(state.height);
+        self.validator_vote_last_seen = state.validator_vote_last_seen;
+        self.validator_missed_periods = state.validator_missed_periods;
+        self.validator_history = state.validator_history;
+        self.feeder_delegation = state.feeder_delegation;
+        self.validator_feeder = state.validator_feeder;
+        self.drift_streaks = state
+            .drift_streaks
+            .into_iter()
+            .map(|(operator_address, denom, streak)| ((operator_address, denom), streak))
+            .collect();
+    }
+
+    /// discard restored state if it is too old to be trusted
+    fn check_restored_state(&mut self, height: u64) {
+        if let Some(restored_height) = self.restored_height.take() {
+            if height > restored_height + self.max_state_age {
+                log::warn!(
+                    "Discarding oracle state from height {}, first block seen is {}",
+                    restored_height,
+                    height
+                );
+                self.validator_vote_last_seen = Default::default();
+                self.validator_missed_periods = Default::default();
+                self.drift_streaks = Default::default();
+            }
+        }
+    }
+
+    fn save_state(&self) {
+        if let Some(path) = &self.state_file {
+            if self.last_height > 0 {
+                if let Err(e) = self.state().save(path) {
+                    log::error!("Unable to save oracle state {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    /// use our own alerting thresholds instead of the chain's reward band
+    pub fn with_thresholds(
+        mut self,
+        default_threshold: Option<DenomThreshold>,
+        denom_thresholds: HashMap<String, DenomThreshold>,
+    ) -> OracleActor {
+        self.default_threshold = default_threshold;
+        self.denom_thresholds = denom_thresholds;
+        self
+    }
+
+    /// the alerting thresholds for a denom. when none are configured, drifts past
+    /// the reward band are warnings and nothing is escalated to critical
+    pub fn threshold(&self, denom: &str) -> DenomThreshold {
+        match self.denom_thresholds.get(denom) {
+            Some(threshold) => threshold.clone(),
+            None => match &self.default_threshold {
+                Some(threshold) => threshold.clone(),
+                None => DenomThreshold {
+                    abstain_floor: Decimal::from_f64(0.001f64).unwrap(),
+                    warning_band: self.reward_band,
+                    critical_band: None,
+                },
+            },
+        }
+    }
+
+    /// create an actor from a parameter snapshot file
+    pub fn from_snapshot<P: AsRef<Path>>(path: P) -> anyhow::Result<OracleActor> {
+        Ok(OracleActor::from_params(OracleParams::load(path)?))
+    }
+
+    pub fn params(&self) -> OracleParams {
+        OracleParams {
+            vote_period: self.vote_period,
+            vote_threshold: self.vote_threshold,
+            reward_band: self.reward_band,
+            reward_distribution_window: self.reward_distribution_window,
+            slash_fraction: self.slash_fraction,
+            slash_window: self.slash_window,
+            min_valid_per_window: self.min_valid_per_window,
+            whitelist: self.whitelist.clone(),
+        }
+    }
+
+    /// apply freshly read parameters, announcing any that differ from the ones in use
+    pub fn apply_params(&mut self, params: OracleParams) {
+        let current = self.params();
+        if current == params {
+            return;
+        }
+        let height = self.last_avg_at_height;
+        let changed = |parameter: &str, previous: String, current: String| {
+            if previous != current {
+                log::warn!(
+                    "Oracle parameter {} changed: {} -> {}",
+                    parameter,
+                    previous,
+                    current
+                );
+                Broker::<SystemBroker>::issue_async(MessageOracleParameterChanged {
+                    height,
+                    parameter: parameter.into(),
+                    previous,
+                    current,
+                });
+            }
+        };
+        changed(
+            "vote_period",
+            current.vote_period.to_string(),
+            params.vote_period.to_string(),
+        );
+        changed(
+            "vote_threshold",
+            current.vote_threshold.to_string(),
+            params.vote_threshold.to_string(),
+        );
+        changed(
+            "reward_band",
+            current.reward_band.to_string(),
+            params.reward_band.to_string(),
+        );
+        changed(
+            "reward_distribution_window",
+            current.reward_distribution_window.to_string(),
+            params.reward_distribution_window.to_string(),
+        );
+        changed(
+            "slash_fraction",
+            current.slash_fraction.to_string(),
+            params.slash_fraction.to_string(),
+        );
+        changed(
+            "slash_window",
+            current.slash_window.to_string(),
+            params.slash_window.to_string(),
+        );
+        changed(
+            "min_valid_per_window",
+            current.min_valid_per_window.to_string(),
+            params.min_valid_per_window.to_string(),
+        );
+        changed(
+            "whitelist",
+            current.whitelist.join(","),
+            params.whitelist.join(","),
+        );
+
+        self.vote_period = params.vote_period;
+        self.vote_threshold = params.vote_threshold;
+        self.reward_band = params.reward_band;
+        self.reward_distribution_window = params.reward_distribution_window;
+        self.slash_fraction = params.slash_fraction;
+        self.slash_window = params.slash_window;
+        self.min_valid_per_window = params.min_valid_per_window;
+        self.whitelist = params.whitelist;
+    }
+
+    /// re-read the oracle parameters from the LCD
+    fn refresh_params(&self, ctx: &mut Context<Self>) {
+        let (lcd, chain) = match &self.lcd {
+            Some(lcd) => lcd.clone(),
+            None => return,
+        };
+        let fut = async move { OracleParams::fetch(&lcd, &chain).await };
+        ctx.spawn(fut.into_actor(self).map(|result, act, _ctx| match result {
+            Ok(params) => act.apply_params(params),
+            Err(e) => log::error!("Unable to refresh oracle parameters: {}", e),
+        }));
+    }
+
+    /// flag votes which omit whitelisted denoms, or include ones that aren't
+    fn check_whitelist(&self, height: u64, operator_address: &str, rates: &[Coin], txhash: &str) {
+        if self.whitelist.is_empty() {
+            return;
+        }
+        let missing = self
+            .whitelist
+            .iter()
+            .filter(|denom| !rates.iter().any(|c| c.denom.eq(*denom)))
+            .cloned()
+            .collect::<Vec<String>>();
+        let unexpected = rates
+            .iter()
+            .filter(|c| !self.whitelist.contains(&c.denom))
+            .map(|c| c.denom.clone())
+            .collect::<Vec<String>>();
+        if !missing.is_empty() || !unexpected.is_empty() {
+            log::info!(
+                "Vote whitelist mismatch {} missing:{:?} unexpected:{:?}",
+                operator_address,
+                missing,
+                unexpected
+            );
+            Broker::<SystemBroker>::issue_async(MessageVoteWhitelistMismatch {
+                height,
+                operator_address: operator_address.into(),
+                missing,
+                unexpected,
+                txhash: txhash.into(),
+            });
+        }
+    }
+
+    /// note the feeder which submitted a vote, and check it is the one the operator delegated to
+    fn record_feeder(&mut self, height: u64, operator_address: &str, feeder: &str, txhash: &str) {
+        if let Some(delegate) = self.feeder_delegation.get(operator_address) {
+            if delegate != feeder {
+                log::warn!(
+                    "Feeder mismatch {} delegated:{} voted:{}",
+                    operator_address,
+                    delegate,
+                    feeder
+                );
+                Broker::<SystemBroker>::issue_async(MessageFeederMismatch {
+                    height,
+                    operator_address: operator_address.into(),
+                    delegated: delegate.clone(),
+                    feeder: feeder.into(),
+                    txhash: txhash.into(),
+                });
+            }
+        }
+        match self
+            .validator_feeder
+            .insert(operator_address.into(), feeder.into())
+        {
+            Some(previous) if previous != feeder => log::info!(
+                "Validator {} feeder changed {} -> {}",
+                operator_address,
+                previous,
+                feeder
+            ),
+            _ => {}
+        }
+    }
+
+    fn record_feeder_delegation(
+        &mut self,
+        height: u64,
+        consent: MsgDelegateFeedConsent,
+        txhash: &str,
+    ) {
+        log::info!(
+            "Feeder delegated {} -> {}",
+            consent.operator,
+            consent.delegate
+        );
+        Broker::<SystemBroker>::issue_async(MessageFeederDelegated {
+            height,
+            operator_address: consent.operator.clone(),
+            feeder: consent.delegate.clone(),
+            txhash: txhash.into(),
+        });
+        self.feeder_delegation
+            .insert(consent.operator, consent.delegate);
+    }
+
+    /// the operator a feeder account is voting for
+    pub fn feeder_validator(&self, feeder: &str) -> Option<String> {
+        self.feeder_delegation
+            .iter()
+            .chain(self.validator_feeder.iter())
+            .find(|f| f.1 == feeder)
+            .map(|f| f.0.clone())
+    }
+
+    /// weighted median of a set of (rate, weight) votes
+    fn weighted_median(votes: &mut [(Decimal, u64)]) -> Option<Decimal> {
+        votes.sort_by(|a, b| a.0.cmp(&b.0));
+        let total: u64 = votes.iter().map(|v| v.1).sum();
+        let mut cumulative: u64 = 0;
+        for (rate, weight) in votes.iter() {
+            cumulative += weight;
+            if cumulative * 2 >= total {
+                return Some(*rate);
+            }
+        }
+        votes.last().map(|v| v.0)
+    }
+
+    /// weighted median of this period's (non-abstaining) votes, per denom
+    pub fn vote_medians(&self) -> HashMap<String, Decimal> {
+        let mut denom_votes: HashMap<String, Vec<(Decimal, u64)>> = Default::default();
+        for (operator_address, rates) in self.validator_vote_prices.iter() {
+            if let Some(weight) = self.validator_weight.get(operator_address) {
+                for coin in rates
+                    .iter()
+                    .filter(|c| c.amount > self.threshold(&c.denom).abstain_floor)
+                {
+                    denom_votes
+                        .entry(coin.denom.clone())
+                        .or_insert_with(Vec::new)
+                        .push((coin.amount, *weight));
+                }
+            }
+        }
+        denom_votes
+            .iter_mut()
+            .filter_map(|(denom, votes)| {
+                Self::weighted_median(votes).map(|median| (denom.clone(), median))
+            })
+            .collect()
+    }
+
+    /// record the outcome of the current vote period for every validator we know of
+    pub fn record_period_history(&mut self, height: u64) {
+        let medians = self.vote_medians();
+        if medians.is_empty() {
+            return;
+        }
+
+        let mut operators: Vec<String> = self.validator_weight.keys().cloned().collect();
+        for operator_address in self
+            .bonded_validators
+            .keys()
+            .chain(self.validator_vote_prices.keys())
+        {
+            if !self.validator_weight.contains_key(operator_address)
+                && !operators.contains(operator_address)
+            {
+                operators.push(operator_address.clone());
+            }
+        }
+        let mut votes: Vec<OraclePeriodVote> = vec![];
+        for operator_address in operators {
+            let record = match self.validator_vote_prices.get(&operator_address) {
+                None => OraclePeriodRecord {
+                    height,
+                    rates: vec![],
+                    deviation: Default::default(),
+                    outcome: OracleVoteOutcome::Miss,
+                },
+                Some(rates) => {
+                    let mut deviation: HashMap<String, Decimal> = Default::default();
+                    let mut abstained = false;
+                    let mut missed = false;
+                    for (denom, median) in medians.iter() {
+                        match rates.iter().find(|c| c.denom.eq(denom)) {
+                            Some(coin) if coin.amount > self.threshold(denom).abstain_floor => {
+                                let drift = (coin.amount - *median).div(*median);
+                                if drift.abs() > self.reward_band {
+                                    missed = true;
+                                }
+                                deviation.insert(denom.clone(), drift);
+                            }
+                            Some(_) => abstained = true,
+                            None => missed = true,
+                        }
+                    }
+                    let outcome = if missed {
+                        OracleVoteOutcome::Miss
+                    } else if abstained {
+                        OracleVoteOutcome::Abstain
+                    } else {
+                        OracleVoteOutcome::Win
+                    };
+                    OraclePeriodRecord {
+                        height,
+                        rates: rates.clone(),
+                        deviation,
+                        outcome,
+                    }
+                }
+            };
+            votes.push(OraclePeriodVote {
+                operator_address: operator_address.clone(),
+                weight: self
+                    .validator_weight
+                    .get(&operator_address)
+                    .cloned()
+                    .unwrap_or_default(),
+                outcome: record.outcome.clone(),
+            });
+            let history = self
+                .validator_history
+                .entry(operator_address)
+                .or_insert_with(VecDeque::new);
+            history.push_back(record);
+            while history.len() > self.history_periods {
+                history.pop_front();
+            }
+        }
+        Broker::<SystemBroker>::issue_async(MessageOracleVotePeriod { height, votes });
+    }
+
+    /// report every bonded validator which did not vote this period.
+    /// if the bonded set is unknown, validators seen voting since startup are used instead
+    fn report_missing_votes(&mut self, height: u64) {
+        log::info!("Seen {} price votes", self.validator_vote_prices.len());
+        let expected: Vec<(String, Option<String>)> = if self.bonded_validators.is_empty() {
+            self.validator_vote_last_seen
+                .keys()
+                .map(|operator_address| (operator_address.clone(), None))
+                .collect()
+        } else {
+            self.bonded_validators
+                .iter()
+                .map(|(operator_address, moniker)| {
+                    (operator_address.clone(), Some(moniker.clone()))
+                })
+                .collect()
+        };
+        for (operator_address, moniker) in expected {
+            if self.validator_vote_prices.contains_key(&operator_address) {
+                self.validator_missed_periods.remove(&operator_address);
+                continue;
+            }
+            let missed = self
+                .validator_missed_periods
+                .entry(operator_address.clone())
+                .or_insert(0);
+            *missed += 1;
+            let consecutive = *missed;
+            let last_seen = self
+                .validator_vote_last_seen
+                .get(&operator_address)
+                .cloned();
+            let message = match last_seen {
+                Some(last_seen) => format!("Operator did not vote. Last Seen:{}", last_seen),
+                None => "Operator did not vote. Not seen voting".into(),
+            };
+            log::info!(
+                "missed vote: {} {} consecutive:{}",
+                operator_address,
+                moniker.clone().unwrap_or_default(),
+                consecutive
+            );
+            Broker::<SystemBroker>::issue_async(MessageOracleMissedVote {
+                height,
+                operator_address: operator_address.clone(),
+                moniker: moniker.clone(),
+                last_seen,
+                consecutive,
+            });
+            // only warn as a validator starts missing, not for every period it stays missing
+            if consecutive == 1 {
+                Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+                    height,
+                    operator_address,
+                    moniker,
+                    event_type: ValidatorEventType::WARN,
+                    message,
+                    hash: None,
+                });
+            }
+        }
+        if self.bonded_validators.is_empty() {
+            // without the bonded set, forget validators not seen voting for the whole history
+            let retention = self.history_periods as u64 * self.vote_period;
+            let cutoff = height.saturating_sub(retention);
+            self.validator_vote_last_seen
+                .retain(|operator_address, last_seen| {
+                    if *last_seen < cutoff {
+                        log::info!("Validator is too old: {}", operator_address);
+                        false
+                    } else {
+                        true
+                    }
+                });
+            let last_seen = &self.validator_vote_last_seen;
+            self.validator_missed_periods
+                .retain(|operator_address, _| last_seen.contains_key(operator_address));
+        } else {
+            let bonded = &self.bonded_validators;
+            self.validator_vote_last_seen
+                .retain(|operator_address, _| bonded.contains_key(operator_address));
+            self.validator_missed_periods
+                .retain(|operator_address, _| bonded.contains_key(operator_address));
+        }
+    }
+
+    /// note how far into the vote period a vote landed, and warn about feeders which
+    /// consistently vote in the last block, where they risk missing the period
+    fn record_vote_timing(&mut self, height: u64, operator_address: &str, txhash: &str) {
+        if self.vote_period == 0 {
+            return;
+        }
+        let offset = height % self.vote_period;
+        let last_block = self.vote_period - 1;
+        let offsets = self
+            .vote_offsets
+            .entry(operator_address.into())
+            .or_insert_with(VecDeque::new);
+        offsets.push_back(offset);
+        while offsets.len() > self.history_periods {
+            offsets.pop_front();
+        }
+        let late = offsets
+            .iter()
+            .rev()
+            .take(self.late_vote_window)
+            .filter(|o| **o == last_block)
+            .count();
+        let recent = offsets.len().min(self.late_vote_window);
+
+        if late >= self.late_vote_count {
+            if self.late_voters.insert(operator_address.into()) {
+                let message = format!(
+                    "Feeder voted in the last block of the period in {} of the last {} votes",
+                    late, recent
+                );
+                log::warn!("Late votes: {} {}", operator_address, message);
+                Broker::<SystemBroker>::issue_async(MessageLateVotes {
+                    height,
+                    operator_address: operator_address.into(),
+                    late,
+                    votes: recent,
+                });
+                Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+                    height,
+                    operator_address: operator_address.into(),
+                    moniker: self.bonded_validators.get(operator_address).cloned(),
+                    event_type: ValidatorEventType::WARN,
+                    message,
+                    hash: Some(txhash.into()),
+                });
+            }
+        } else {
+            self.late_voters.remove(operator_address);
+        }
+    }
+
+    /// distribution of a validator's recent vote offsets within the vote period
+    pub fn vote_timing(&self, operator_address: &str) -> Option<VoteTiming> {
+        let offsets = self.vote_offsets.get(operator_address)?;
+        if offsets.is_empty() || self.vote_period == 0 {
+            return None;
+        }
+        let mut histogram = vec![0; self.vote_period as usize];
+        for offset in offsets.iter() {
+            if let Some(bucket) = histogram.get_mut(*offset as usize) {
+                *bucket += 1;
+            }
+        }
+        let sum: u64 = offsets.iter().sum();
+        Some(VoteTiming {
+            operator_address: operator_address.into(),
+            votes: offsets.len(),
+            mean_offset: Decimal::from(sum).div(Decimal::from(offsets.len())),
+            last_block_votes: histogram.last().cloned().unwrap_or_default(),
+            histogram,
+        })
+    }
+
+    /// summarize the last `periods` vote periods of a validator
+    pub fn performance(&self, operator_address: &str, periods: usize) -> Option<OraclePerformance> {
+        let history = self.validator_history.get(operator_address)?;
+        let records = history
+            .iter()
+            .rev()
+            .take(periods)
+            .collect::<Vec<&OraclePeriodRecord>>();
+        if records.is_empty() {
+            return None;
+        }
+        let count =
+            |outcome: OracleVoteOutcome| records.iter().filter(|r| r.outcome == outcome).count();
+        let wins = count(OracleVoteOutcome::Win);
+        let misses = count(OracleVoteOutcome::Miss);
+        let abstains = count(OracleVoteOutcome::Abstain);
+
+        let mut deviation_sum: HashMap<String, (Decimal, usize)> = Default::default();
+        for record in records.iter() {
+            for (denom, deviation) in record.deviation.iter() {
+                let entry = deviation_sum
+                    .entry(denom.clone())
+                    .or_insert((Decimal::ZERO, 0));
+                entry.0 += deviation.abs();
+                entry.1 += 1;
+            }
+        }
+        let mean_abs_deviation = deviation_sum
+            .into_iter()
+            .map(|(denom, (sum, n))| (denom, sum.div(Decimal::from(n))))
+            .collect::<HashMap<String, Decimal>>();
+
+        Some(OraclePerformance {
+            operator_address: operator_address.into(),
+            periods: records.len(),
+            from_height: records.last().map(|r| r.height).unwrap_or_default(),
+            to_height: records.first().map(|r| r.height).unwrap_or_default(),
+            wins,
+            misses,
+            abstains,
+            win_rate: Decimal::from(wins).div(Decimal::from(records.len())),
+            mean_abs_deviation,
         })
     }
 
@@ -67,7 +962,7 @@ impl OracleActor {
                 let validator_prices = v_vote.1;
                 if let Some(weight) = self.validator_weight.get(operator_address) {
                     validator_prices.iter().for_each(|coin| {
-                        if coin.amount > Decimal::from_f64(0.001f64).unwrap() {
+                        if coin.amount > self.threshold(&coin.denom).abstain_floor {
                             let updated = match agg_price.get(&coin.denom) {
                                 None => (
                                     1,
@@ -100,6 +995,9 @@ impl OracleActor {
                 }
             });
 
+            let medians = self.vote_medians();
+            let mut drifts: Vec<MessagePriceDrift> = vec![];
+            let mut in_band: Vec<(String, String)> = vec![];
             let averages: HashMap<String, (Decimal, Decimal)> = agg_price
                 .iter()
                 .map(|f| {
@@ -110,7 +1008,6 @@ impl OracleActor {
                     (f.0.clone(), (avg_price, avg_weighted_price))
                 })
                 .collect();
-            let drift_max_percentage: Decimal = self.reward_band;
             averages.iter().for_each(|f| {
                 if f.0 == "uusd" {
                     log::info!("{} AVG:{:.4}\t Weighted:{:.4}", f.0, f.1 .0, f.1 .1)
@@ -122,8 +1019,15 @@ impl OracleActor {
                 let denom = f.0;
                 let average_price = f.1 .0;
                 let average_weighted_price = f.1 .1;
-                let drift_max = average_price.mul(drift_max_percentage).abs();
-                let _drift_weighted_max = average_weighted_price.mul(drift_max_percentage).abs();
+                let median_price = medians.get(denom).cloned().unwrap_or(average_price);
+                let threshold = self.threshold(denom);
+                let warning_max = median_price.mul(threshold.warning_band).abs();
+                if warning_max.is_zero() {
+                    return;
+                }
+                let critical_max = threshold
+                    .critical_band
+                    .map(|band| median_price.mul(band).abs());
 
                 self.validator_vote_prices
                     .iter()
@@ -137,9 +1041,17 @@ impl OracleActor {
                             .first()
                         {
                             let submitted_price = price_submitted_coin.amount;
-                            if submitted_price > Decimal::from_f64(0.001f64).unwrap() {
-                                let drift = submitted_price - average_price;
-                                if drift.abs() > drift_max {
+                            if submitted_price > threshold.abstain_floor {
+                                let drift = submitted_price - median_price;
+                                if drift.abs() > warning_max {
+                                    let severity = if critical_max
+                                        .map(|critical_max| drift.abs() > critical_max)
+                                        .unwrap_or(false)
+                                    {
+                                        DriftSeverity::Critical
+                                    } else {
+                                        DriftSeverity::Warning
+                                    };
                                     let txhash = self
                                         .validator_vote_last_hash
                                         .get(&operator_address)
@@ -147,26 +1059,33 @@ impl OracleActor {
                                         .unwrap_or_else(|| "-missing hash-".into());
 
                                     log::debug!(
-                                        "Drift detected {} {} {} {:4}/{:4} {}/{} - {}",
+                                        "Drift detected {:?} {} {} {} {:4}/{:4}/{:4} {}/{} - {}",
+                                        severity,
                                         operator_address,
                                         denom,
                                         submitted_price,
+                                        median_price,
                                         average_price,
                                         average_weighted_price,
                                         drift,
-                                        drift_max,
+                                        warning_max,
                                         txhash
                                     );
 
-                                    Broker::<SystemBroker>::issue_async(MessagePriceDrift {
+                                    drifts.push(MessagePriceDrift {
                                         height,
                                         operator_address,
                                         denom: denom.into(),
                                         average: average_price,
                                         weighted_average: average_weighted_price,
+                                        median: median_price,
                                         submitted: submitted_price,
+                                        severity,
+                                        band_multiple: drift.abs().div(warning_max),
                                         txhash,
                                     });
+                                } else {
+                                    in_band.push((operator_address, denom.clone()));
                                 }
                             }
                         } else {
@@ -174,6 +1093,103 @@ impl OracleActor {
                         }
                     })
             });
+            self.process_drift_streaks(height, drifts, in_band);
+        } else {
+            // nobody voted, so every streak ends
+            self.process_drift_streaks(height, vec![], vec![]);
+        }
+    }
+
+    /// alert when a validator starts drifting on a denom (or its drift becomes critical),
+    /// summarize while it continues, and announce when it is back within the band.
+    /// a streak ends in any period without an out-of-band vote for the denom
+    fn process_drift_streaks(
+        &mut self,
+        height: u64,
+        drifts: Vec<MessagePriceDrift>,
+        in_band: Vec<(String, String)>,
+    ) {
+        let drifting: HashSet<(String, String)> = drifts
+            .iter()
+            .map(|drift| (drift.operator_address.clone(), drift.denom.clone()))
+            .collect();
+        let ended: Vec<(String, String)> = self
+            .drift_streaks
+            .keys()
+            .filter(|key| !drifting.contains(*key))
+            .cloned()
+            .collect();
+        for key in ended {
+            if let Some(streak) = self.drift_streaks.remove(&key) {
+                if in_band.contains(&key) {
+                    log::info!(
+                        "Drift recovered {} {} after {} periods",
+                        key.0,
+                        key.1,
+                        streak.periods
+                    );
+                    Broker::<SystemBroker>::issue_async(MessagePriceDriftRecovered {
+                        height,
+                        operator_address: key.0,
+                        denom: key.1,
+                        start_height: streak.start_height,
+                        periods: streak.periods,
+                        worst_severity: streak.worst_severity,
+                    });
+                } else {
+                    log::debug!(
+                        "Drift streak ended {} {} after {} periods, no vote in band",
+                        key.0,
+                        key.1,
+                        streak.periods
+                    );
+                }
+            }
+        }
+        for drift in drifts {
+            let key = (drift.operator_address.clone(), drift.denom.clone());
+            match self.drift_streaks.entry(key) {
+                Entry::Vacant(v) => {
+                    v.insert(DriftStreak {
+                        start_height: height,
+                        periods: 1,
+                        worst_severity: drift.severity.clone(),
+                        max_band_multiple: drift.band_multiple,
+                    });
+                    Broker::<SystemBroker>::issue_async(drift);
+                }
+                Entry::Occupied(mut e) => {
+                    let streak = e.get_mut();
+                    streak.periods += 1;
+                    if drift.band_multiple > streak.max_band_multiple {
+                        streak.max_band_multiple = drift.band_multiple;
+                    }
+                    if drift.severity == DriftSeverity::Critical
+                        && streak.worst_severity != DriftSeverity::Critical
+                    {
+                        // escalations are alerted straight away
+                        streak.worst_severity = DriftSeverity::Critical;
+                        Broker::<SystemBroker>::issue_async(drift);
+                        continue;
+                    }
+                    if self.drift_summary_periods > 0
+                        && streak.periods % self.drift_summary_periods == 0
+                    {
+                        Broker::<SystemBroker>::issue_async(MessagePriceDriftOngoing {
+                            height,
+                            operator_address: drift.operator_address,
+                            denom: drift.denom,
+                            start_height: streak.start_height,
+                            periods: streak.periods,
+                            worst_severity: streak.worst_severity.clone(),
+                            max_band_multiple: streak.max_band_multiple,
+                            median: drift.median,
+                            submitted: drift.submitted,
+                            txhash: drift.txhash,
+                        });
+                    }
+                }
+            }
         }
     }
 }
@@ -183,7 +1199,15 @@ impl Actor for OracleActor {
     fn started(&mut self, ctx: &mut Self::Context) {
         self.subscribe_sync::<BrokerType, MessageTX>(ctx);
         self.subscribe_sync::<BrokerType, MessageValidatorStakedTotal>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidator>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidatorRemoved>(ctx);
         self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        if self.lcd.is_some() {
+            ctx.run_interval(self.parameter_refresh, |act, ctx| act.refresh_params(ctx));
+        }
+        if self.state_file.is_some() {
+            ctx.run_interval(self.state_save_interval, |act, _ctx| act.save_state());
+        }
     }
 }
 
@@ -192,10 +1216,88 @@ impl Handler<MessageStop> for OracleActor {
 
     fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
         log::info!("Oracle Actor Stopping");
+        self.save_state();
         ctx.stop()
     }
 }
 
+impl Handler<GetOraclePerformance> for OracleActor {
+    type Result = MessageResult<GetOraclePerformance>;
+
+    fn handle(&mut self, msg: GetOraclePerformance, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.performance(&msg.operator, msg.periods))
+    }
+}
+
+impl Handler<GetValidatorFeeder> for OracleActor {
+    type Result = MessageResult<GetValidatorFeeder>;
+
+    fn handle(&mut self, msg: GetValidatorFeeder, _ctx: &mut Self::Context) -> Self::Result {
+        let delegated = self.feeder_delegation.get(&msg.operator).cloned();
+        let observed = self.validator_feeder.get(&msg.operator).cloned();
+        if delegated.is_none() && observed.is_none() {
+            MessageResult(None)
+        } else {
+            MessageResult(Some(ValidatorFeeder {
+                operator_address: msg.operator,
+                delegated,
+                observed,
+            }))
+        }
+    }
+}
+
+impl Handler<GetVoteTiming> for OracleActor {
+    type Result = MessageResult<GetVoteTiming>;
+
+    fn handle(&mut self, msg: GetVoteTiming, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.vote_timing(&msg.operator))
+    }
+}
+
+impl Handler<GetFeederValidator> for OracleActor {
+    type Result = MessageResult<GetFeederValidator>;
+
+    fn handle(&mut self, msg: GetFeederValidator, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.feeder_validator(&msg.feeder))
+    }
+}
+
+impl Handler<MessageValidator> for OracleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidator, _ctx: &mut Self::Context) {
+        // only validators in the tendermint validator set are expected to vote. jailed and
+        // unbonding validators are refreshed too, which removes them
+        if msg.tendermint.is_some() && !msg.validator.jailed {
+            self.bonded_validators.insert(
+                msg.operator_address,
+                msg.validator.description.moniker.clone(),
+            );
+        } else if self
+            .bonded_validators
+            .remove(&msg.operator_address)
+            .is_some()
+        {
+            log::info!("Validator {} no longer bonded", msg.operator_address);
+        }
+    }
+}
+
+impl Handler<MessageValidatorRemoved> for OracleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidatorRemoved, _ctx: &mut Self::Context) {
+        if self
+            .bonded_validators
+            .remove(&msg.operator_address)
+            .is_some()
+        {
+            log::info!("Validator {} removed", msg.operator_address);
+        }
+    }
+}
+
 impl Handler<MessageValidatorStakedTotal> for OracleActor {
     type Result = ();
 
@@ -220,6 +1322,8 @@ impl Handler<MessageTX> for OracleActor {
 
     fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
         let height = msg.tx.height;
+        self.check_restored_state(height);
+        self.last_height = height;
         if msg.tx.tx.s_type == "/cosmos.tx.v1beta1.Tx" {
             // if msg.tx.tx.s_type == "core/StdTx" {
             let messages = msg.tx.tx.body;
@@ -234,6 +1338,19 @@ impl Handler<MessageTX> for OracleActor {
                                 //  log::info!("Vote {} {}", vote.validator, vote.feeder);
                                 match Coin::parse_coins(&vote.exchange_rates) {
                                     Ok(rates) => {
+                                        self.record_vote_timing(height, &vote.validator, &txhash);
+                                        self.record_feeder(
+                                            height,
+                                            &vote.validator,
+                                            &vote.feeder,
+                                            &txhash,
+                                        );
+                                        self.check_whitelist(
+                                            height,
+                                            &vote.validator,
+                                            &rates,
+                                            &txhash,
+                                        );
                                         self.validator_vote_last_seen
                                             .insert(vote.validator.clone(), height);
                                         self.validator_vote_prices
@@ -253,6 +1370,13 @@ impl Handler<MessageTX> for OracleActor {
                             }
                             Err(e) => log::error!("Expected vote: {} - {}", e, m.to_string()),
                         }
+                    } else if message_type == "/terra.oracle.v1beta1.MsgDelegateFeedConsent" {
+                        match serde_json::from_value::<MsgDelegateFeedConsent>(m.clone()) {
+                            Ok(consent) => self.record_feeder_delegation(height, consent, &txhash),
+                            Err(e) => {
+                                log::error!("Expected feed consent: {} - {}", e, m.to_string())
+                            }
+                        }
                     } else {
                         log::debug!("{} -- {} ", height, message_type);
                     }
@@ -302,46 +1426,13 @@ impl Handler<MessageTX> for OracleActor {
                 msg.tx.tx.body
             );
         }
-        // make 'laggy' be 2 vote periods
         if height >= self.last_avg_at_height + self.vote_period {
             self.do_price_averages(height);
-            let laggy_height = if self.vote_period < self.last_avg_at_height {
-                self.last_avg_at_height - self.vote_period
-            } else {
-                0
-            };
-            let laggy = self
-                .validator_vote_last_seen
-                .iter()
-                .filter(|f| f.1 < &laggy_height)
-                .collect::<Vec<_>>();
-            log::info!("Seen {} price votes", self.validator_vote_prices.len());
-            if !laggy.is_empty() {
-                laggy.iter().for_each(|f| {
-                    let message = format!("Operator missed a vote? Last Seen:{}", f.1);
-                    log::info!("laggy: {} Last Seen:{}", f.0, f.1);
-                    Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
-                        height,
-                        operator_address: f.0.clone(),
-                        moniker: None,
-                        event_type: ValidatorEventType::WARN,
-                        message,
-                        hash: None,
-                    });
-                })
+            self.record_period_history(height);
+            // the first period after startup is only partially observed
+            if self.last_avg_at_height > 0 {
+                self.report_missing_votes(height);
             }
-            self.validator_vote_last_seen = self
-                .validator_vote_last_seen
-                .iter()
-                .flat_map(|f| {
-                    if f.1 < &(height - 100) {
-                        log::info!("Validator is too old: {}", f.0);
-                        None
-                    } else {
-                        Some((f.0.clone(), *f.1))
-                    }
-                })
-                .collect();
 
             self.validator_vote_prices = Default::default();
             self.validator_vote_last_hash = Default::default();
@@ -349,3 +1440,54 @@ impl Handler<MessageTX> for OracleActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    #[cfg(feature = "toml-snapshots")]
+    fn params_toml_matches_json() {
+        let toml = OracleParams::load(fixture("oracle_params.toml")).unwrap();
+        let json = OracleParams::load(fixture("oracle_params.json")).unwrap();
+        assert_eq!(toml, json);
+        assert_eq!(toml.vote_period, 5);
+        assert_eq!(toml.vote_threshold, dec!(0.5));
+        assert_eq!(toml.whitelist, vec!["ukrw", "usdr", "uusd"]);
+    }
+
+    #[test]
+    #[cfg(feature = "toml-snapshots")]
+    fn params_save_round_trip() {
+        let params = OracleParams::load(fixture("oracle_params.json")).unwrap();
+        let path = std::env::temp_dir().join(format!("oracle_params_{}.toml", std::process::id()));
+        params.save(&path).unwrap();
+        let loaded = OracleParams::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), params);
+    }
+
+    #[test]
+    fn weighted_median_by_weight() {
+        let mut votes = vec![(dec!(3), 1), (dec!(1), 1), (dec!(2), 1)];
+        assert_eq!(OracleActor::weighted_median(&mut votes), Some(dec!(2)));
+        // a heavy vote pulls the median to it
+        let mut votes = vec![(dec!(1), 1), (dec!(2), 1), (dec!(10), 5)];
+        assert_eq!(OracleActor::weighted_median(&mut votes), Some(dec!(10)));
+        // exactly half the weight at or below the rate is enough
+        let mut votes = vec![(dec!(1), 2), (dec!(2), 2)];
+        assert_eq!(OracleActor::weighted_median(&mut votes), Some(dec!(1)));
+    }
+
+    #[test]
+    fn weighted_median_empty() {
+        assert_eq!(OracleActor::weighted_median(&mut []), None);
+    }
+}
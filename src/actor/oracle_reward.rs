@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::ops::{Div, Mul};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use rust_decimal::Decimal;
+use terra_rust_api::core_types::Coin;
+use terra_rust_api::Terra;
+
+use crate::actor::OracleParams;
+use crate::messages::{
+    MessageBlockEventCommission, MessageBlockEventReward, MessageOracleParameterChanged,
+    MessageOracleRewardEstimate, MessageOracleRewardPool, MessageOracleVotePeriod,
+    OracleVoteOutcome,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+use std::time::Duration;
+
+/// the oracle module account, which holds the reward pool
+pub const ORACLE_MODULE_ACCOUNT: &str = "terra1jgp27m8fykex4e4jtt0l7ze8q528ux2lh4zh0f";
+/// number of vote periods between estimates
+pub const DEFAULT_REPORT_PERIODS: u64 = 100;
+/// how often the reward pool is re-read from the LCD
+pub const DEFAULT_POOL_REFRESH: Duration = Duration::from_secs(600);
+
+/// projected & actual rewards of a validator for the current report
+#[derive(Clone, Debug, Default)]
+pub struct OracleRewardTally {
+    pub wins: u64,
+    pub projected: HashMap<String, Decimal>,
+    pub actual: HashMap<String, Decimal>,
+}
+
+/// projects each validator's share of the oracle reward pool from its wins, and compares
+/// it to the rewards (and commission) distributed to it at the end of blocks, which is when
+/// the oracle pays out.
+/// each vote period pays out `pool * vote_period / reward_distribution_window`,
+/// split between the winners by voting power
+pub struct OracleRewardActor {
+    pub vote_period: u64,
+    pub reward_distribution_window: u64,
+    pub pool: Vec<Coin>,
+    /// LCD & chain used to refresh the reward pool. None when running offline
+    pub lcd: Option<(String, String)>,
+    pub pool_refresh: Duration,
+    pub report_periods: u64,
+    pub periods: u64,
+    pub from_height: u64,
+    pub last_height: u64,
+    pub tallies: HashMap<String, OracleRewardTally>,
+}
+impl OracleRewardActor {
+    pub fn create(params: &OracleParams, lcd: Option<(String, String)>) -> OracleRewardActor {
+        OracleRewardActor {
+            vote_period: params.vote_period,
+            reward_distribution_window: params.reward_distribution_window,
+            pool: vec![],
+            lcd,
+            pool_refresh: DEFAULT_POOL_REFRESH,
+            report_periods: DEFAULT_REPORT_PERIODS,
+            periods: 0,
+            from_height: 0,
+            last_height: 0,
+            tallies: Default::default(),
+        }
+    }
+
+    fn refresh_pool(&self, ctx: &mut Context<Self>) {
+        let (lcd, chain) = match &self.lcd {
+            Some(lcd) => lcd.clone(),
+            None => return,
+        };
+        let fut = async move {
+            let terra = Terra::lcd_client_no_tx(&lcd, &chain).await?;
+            Ok::<Vec<Coin>, anyhow::Error>(
+                terra.bank().balances(ORACLE_MODULE_ACCOUNT).await?.result,
+            )
+        };
+        ctx.spawn(fut.into_actor(self).map(|result, act, _ctx| match result {
+            Ok(pool) => Broker::<SystemBroker>::issue_async(MessageOracleRewardPool {
+                height: act.last_height,
+                pool,
+            }),
+            Err(e) => log::error!("Unable to refresh oracle reward pool: {}", e),
+        }));
+    }
+
+    /// rewards paid out in a single vote period, by denom
+    fn period_rewards(&self) -> Vec<(String, Decimal)> {
+        if self.reward_distribution_window == 0 {
+            return vec![];
+        }
+        let fraction =
+            Decimal::from(self.vote_period).div(Decimal::from(self.reward_distribution_window));
+        self.pool
+            .iter()
+            .map(|coin| (coin.denom.clone(), coin.amount.mul(fraction)))
+            .collect()
+    }
+
+    fn add_actual(&mut self, validator: String, amount: Vec<Coin>) {
+        let tally = self.tallies.entry(validator).or_default();
+        for coin in amount {
+            *tally.actual.entry(coin.denom).or_insert(Decimal::ZERO) += coin.amount;
+        }
+    }
+
+    fn publish(&mut self, height: u64) {
+        for (operator_address, tally) in self.tallies.drain() {
+            log::debug!(
+                "Oracle reward estimate {} wins:{} projected:{:?} actual:{:?}",
+                operator_address,
+                tally.wins,
+                tally.projected,
+                tally.actual
+            );
+            Broker::<SystemBroker>::issue_async(MessageOracleRewardEstimate {
+                from_height: self.from_height,
+                to_height: height,
+                operator_address,
+                periods: self.periods,
+                wins: tally.wins,
+                projected: tally.projected,
+                actual: tally.actual,
+            });
+        }
+        self.periods = 0;
+        self.from_height = height;
+    }
+}
+impl Actor for OracleRewardActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageOracleVotePeriod>(ctx);
+        self.subscribe_sync::<BrokerType, MessageOracleRewardPool>(ctx);
+        self.subscribe_sync::<BrokerType, MessageOracleParameterChanged>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventReward>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventCommission>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        if self.lcd.is_some() {
+            self.refresh_pool(ctx);
+            ctx.run_interval(self.pool_refresh, |act, ctx| act.refresh_pool(ctx));
+        }
+    }
+}
+
+impl Handler<MessageStop> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Oracle Reward Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageOracleRewardPool> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageOracleRewardPool, _ctx: &mut Self::Context) {
+        self.pool = msg.pool;
+    }
+}
+
+impl Handler<MessageOracleParameterChanged> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageOracleParameterChanged, _ctx: &mut Self::Context) {
+        match msg.parameter.as_str() {
+            "vote_period" => {
+                if let Ok(vote_period) = msg.current.parse() {
+                    self.vote_period = vote_period
+                }
+            }
+            "reward_distribution_window" => {
+                if let Ok(window) = msg.current.parse() {
+                    self.reward_distribution_window = window
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Handler<MessageBlockEventReward> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventReward, _ctx: &mut Self::Context) {
+        // staking & fee rewards are distributed in the begin block
+        if msg.is_proposer || msg.is_begin {
+            return;
+        }
+        self.add_actual(msg.validator, msg.amount);
+    }
+}
+
+impl Handler<MessageBlockEventCommission> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventCommission, _ctx: &mut Self::Context) {
+        if msg.is_begin {
+            return;
+        }
+        self.add_actual(msg.validator, msg.amount);
+    }
+}
+
+impl Handler<MessageOracleVotePeriod> for OracleRewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageOracleVotePeriod, _ctx: &mut Self::Context) {
+        if self.from_height == 0 {
+            self.from_height = msg.height;
+        }
+        self.last_height = msg.height;
+        let winners = msg
+            .votes
+            .iter()
+            .filter(|v| v.outcome == OracleVoteOutcome::Win)
+            .collect::<Vec<_>>();
+        let winning_power: u64 = winners.iter().map(|v| v.weight).sum();
+        let period_rewards = self.period_rewards();
+        for winner in winners {
+            let tally = self
+                .tallies
+                .entry(winner.operator_address.clone())
+                .or_default();
+            tally.wins += 1;
+            if winning_power > 0 {
+                let share = Decimal::from(winner.weight).div(Decimal::from(winning_power));
+                for (denom, amount) in period_rewards.iter() {
+                    *tally
+                        .projected
+                        .entry(denom.clone())
+                        .or_insert(Decimal::ZERO) += amount.mul(share);
+                }
+            }
+        }
+        self.periods += 1;
+        if self.periods >= self.report_periods {
+            self.publish(msg.height);
+        }
+    }
+}
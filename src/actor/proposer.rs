@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::ops::{Div, Mul};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use rust_decimal::Decimal;
+
+use crate::address::bech32_to_hex;
+use crate::messages::{
+    GetProposerStat, MessageBlockProposed, MessageProposerStats, MessageValidator,
+    MessageValidatorRemoved, ProposerStat,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// number of blocks between proposer statistics
+pub const DEFAULT_PROPOSER_REPORT_BLOCKS: u64 = 14400;
+
+/// compares how often each validator proposes with how often its voting power says it should.
+/// validators are keyed by consensus address, as that is what block headers carry
+pub struct ProposerActor {
+    pub report_blocks: u64,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub blocks: u64,
+    /// blocks proposed in the current window, by consensus address (hex)
+    pub proposed: HashMap<String, u64>,
+    /// voting power, by consensus address (hex)
+    pub power: HashMap<String, u64>,
+    /// operator address, by consensus address (hex)
+    pub operators: HashMap<String, String>,
+}
+impl ProposerActor {
+    pub fn create(report_blocks: u64) -> ProposerActor {
+        ProposerActor {
+            report_blocks,
+            from_height: 0,
+            to_height: 0,
+            blocks: 0,
+            proposed: Default::default(),
+            power: Default::default(),
+            operators: Default::default(),
+        }
+    }
+
+    pub fn stat(&self, consensus_address: &str) -> ProposerStat {
+        let total_power: u64 = self.power.values().sum();
+        let power = self
+            .power
+            .get(consensus_address)
+            .cloned()
+            .unwrap_or_default();
+        let expected = if total_power == 0 {
+            Decimal::ZERO
+        } else {
+            Decimal::from(self.blocks)
+                .mul(Decimal::from(power))
+                .div(Decimal::from(total_power))
+        };
+        ProposerStat {
+            consensus_address: consensus_address.into(),
+            operator_address: self.operators.get(consensus_address).cloned(),
+            power,
+            proposed: self
+                .proposed
+                .get(consensus_address)
+                .cloned()
+                .unwrap_or_default(),
+            expected,
+        }
+    }
+
+    fn consensus_address(&self, operator_address: &str) -> Option<String> {
+        self.operators
+            .iter()
+            .find(|(_, operator)| *operator == operator_address)
+            .map(|(consensus_address, _)| consensus_address.clone())
+    }
+
+    fn publish(&mut self) {
+        let mut addresses = self.power.keys().cloned().collect::<Vec<_>>();
+        for address in self.proposed.keys() {
+            if !self.power.contains_key(address) {
+                addresses.push(address.clone());
+            }
+        }
+        addresses.sort();
+        let validators = addresses
+            .iter()
+            .map(|address| self.stat(address))
+            .collect::<Vec<_>>();
+        log::info!(
+            "Proposer stats {}-{} {} blocks",
+            self.from_height,
+            self.to_height,
+            self.blocks
+        );
+        Broker::<SystemBroker>::issue_async(MessageProposerStats {
+            from_height: self.from_height,
+            to_height: self.to_height,
+            blocks: self.blocks,
+            validators,
+        });
+        self.proposed = Default::default();
+        self.blocks = 0;
+    }
+}
+impl Actor for ProposerActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidator>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidatorRemoved>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for ProposerActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Proposer Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageValidator> for ProposerActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidator, _ctx: &mut Self::Context) {
+        match msg
+            .tendermint
+            .as_ref()
+            .and_then(|v| bech32_to_hex(&v.address).map(|hex| (hex, v.voting_power)))
+        {
+            Some((consensus_address, voting_power)) => {
+                self.operators
+                    .insert(consensus_address.clone(), msg.operator_address);
+                self.power.insert(consensus_address, voting_power);
+            }
+            // no longer in the validator set
+            None => {
+                if let Some(consensus_address) = self.consensus_address(&msg.operator_address) {
+                    self.power.remove(&consensus_address);
+                }
+            }
+        }
+    }
+}
+
+impl Handler<MessageValidatorRemoved> for ProposerActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidatorRemoved, _ctx: &mut Self::Context) {
+        if let Some(consensus_address) = self.consensus_address(&msg.operator_address) {
+            self.power.remove(&consensus_address);
+        }
+    }
+}
+
+impl Handler<MessageBlockProposed> for ProposerActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        if self.blocks == 0 {
+            self.from_height = msg.height;
+        }
+        if let Some(operator_address) = msg.operator_address {
+            self.operators
+                .entry(msg.proposer_address.clone())
+                .or_insert(operator_address);
+        }
+        *self.proposed.entry(msg.proposer_address).or_insert(0) += 1;
+        self.blocks += 1;
+        self.to_height = msg.height;
+        if self.blocks >= self.report_blocks {
+            self.publish();
+        }
+    }
+}
+
+impl Handler<GetProposerStat> for ProposerActor {
+    type Result = MessageResult<GetProposerStat>;
+
+    fn handle(&mut self, msg: GetProposerStat, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(
+            self.consensus_address(&msg.operator)
+                .map(|consensus_address| self.stat(&consensus_address)),
+        )
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::Div;
+use std::path::Path;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::messages::{
+    MessageBlockEventExchangeRate, MessageReferencePriceConverged, MessageReferencePriceDivergence,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+use std::time::Duration;
+
+/// where a denom's price is found in the reference feed's response
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReferencePriceMapping {
+    /// JSON pointer (RFC 6901) to the price, eg. "/rates/KRW"
+    pub pointer: String,
+    /// the feed quotes the denom in LUNA, rather than LUNA in the denom
+    #[serde(default)]
+    pub invert: bool,
+}
+/// a JSON endpoint providing reference prices
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReferencePriceSource {
+    pub url: String,
+    /// seconds between polls
+    pub interval: u64,
+    /// seconds after which a reference price is too old to compare against
+    pub max_age: u64,
+    /// alert when the chain's rate is further than this fraction from the reference price
+    pub threshold: Decimal,
+    /// mapping by denom
+    pub denoms: HashMap<String, ReferencePriceMapping>,
+}
+impl ReferencePriceSource {
+    /// load a source definition from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<ReferencePriceSource> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// extract the mapped prices from a response
+    pub fn prices(&self, response: &Value) -> HashMap<String, Decimal> {
+        self.denoms
+            .iter()
+            .filter_map(|(denom, mapping)| {
+                let price = match response.pointer(&mapping.pointer)? {
+                    Value::String(s) => Decimal::from_str(s).ok(),
+                    Value::Number(n) => n.as_f64().and_then(Decimal::from_f64),
+                    _ => None,
+                };
+                match price {
+                    Some(price) if price.is_zero() => None,
+                    Some(price) if mapping.invert => Some((denom.clone(), Decimal::ONE.div(price))),
+                    Some(price) => Some((denom.clone(), price)),
+                    None => {
+                        log::warn!(
+                            "Reference price for {} not found at {}",
+                            denom,
+                            mapping.pointer
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+/// compares the chain's oracle rates against a reference price feed
+pub struct ReferencePriceActor {
+    pub source: ReferencePriceSource,
+    pub client: reqwest::Client,
+    /// latest reference price, by denom
+    pub reference: HashMap<String, (DateTime<Utc>, Decimal)>,
+    /// latest on-chain rate, by denom
+    pub exchange_rates: HashMap<String, (u64, Decimal)>,
+    /// denoms currently diverging
+    pub diverged: HashSet<String>,
+}
+impl ReferencePriceActor {
+    pub fn create(source: ReferencePriceSource) -> ReferencePriceActor {
+        ReferencePriceActor {
+            source,
+            client: reqwest::Client::new(),
+            reference: Default::default(),
+            exchange_rates: Default::default(),
+            diverged: Default::default(),
+        }
+    }
+
+    fn refresh(&self, ctx: &mut Context<Self>) {
+        let request = self.client.get(&self.source.url);
+        let fut = async move {
+            let response = request.send().await?.error_for_status()?;
+            Ok::<Value, reqwest::Error>(response.json::<Value>().await?)
+        };
+        ctx.spawn(fut.into_actor(self).map(|result, act, _ctx| match result {
+            Ok(response) => {
+                let now = Utc::now();
+                for (denom, price) in act.source.prices(&response) {
+                    act.reference.insert(denom.clone(), (now, price));
+                    act.compare(&denom);
+                }
+            }
+            Err(e) => log::error!("Unable to fetch reference prices {}: {}", act.source.url, e),
+        }));
+    }
+
+    fn compare(&mut self, denom: &str) {
+        let (height, exchange_rate) = match self.exchange_rates.get(denom) {
+            Some(rate) => *rate,
+            None => return,
+        };
+        let (reference_time, reference_rate) = match self.reference.get(denom) {
+            Some(reference) => *reference,
+            None => return,
+        };
+        let age = Utc::now() - reference_time;
+        if age > chrono::Duration::seconds(self.source.max_age as i64) {
+            log::debug!(
+                "Reference price for {} is stale ({}s old), not comparing",
+                denom,
+                age.num_seconds()
+            );
+            return;
+        }
+        let divergence = (exchange_rate - reference_rate).div(reference_rate);
+        if divergence.abs() > self.source.threshold {
+            if self.diverged.insert(denom.into()) {
+                log::warn!(
+                    "Oracle rate diverges from reference {} {} {} {:.4}",
+                    denom,
+                    exchange_rate,
+                    reference_rate,
+                    divergence
+                );
+                Broker::<SystemBroker>::issue_async(MessageReferencePriceDivergence {
+                    height,
+                    denom: denom.into(),
+                    exchange_rate,
+                    reference_rate,
+                    reference_time,
+                    divergence,
+                });
+            }
+        } else if self.diverged.remove(denom) {
+            log::info!("Oracle rate back within reference {}", denom);
+            Broker::<SystemBroker>::issue_async(MessageReferencePriceConverged {
+                height,
+                denom: denom.into(),
+                exchange_rate,
+                reference_rate,
+                divergence,
+            });
+        }
+    }
+}
+impl Actor for ReferencePriceActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockEventExchangeRate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        self.refresh(ctx);
+        ctx.run_interval(
+            Duration::from_secs(self.source.interval.max(1)),
+            |act, ctx| act.refresh(ctx),
+        );
+    }
+}
+
+impl Handler<MessageStop> for ReferencePriceActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Reference Price Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockEventExchangeRate> for ReferencePriceActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventExchangeRate, _ctx: &mut Self::Context) {
+        if self.source.denoms.contains_key(&msg.denom) {
+            self.exchange_rates
+                .insert(msg.denom.clone(), (msg.height, msg.exchange_rate));
+            self.compare(&msg.denom);
+        }
+    }
+}
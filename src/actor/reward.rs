@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::ops::{Div, Mul};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use terra_rust_api::core_types::Coin;
+
+use crate::messages::{
+    GetRewardSummary, MessageBlockEventCommission, MessageBlockEventExchangeRate,
+    MessageBlockEventReward, MessageRewardSummary, ValidatorRewardSummary,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// how long rewards are accumulated for before a summary is published
+#[derive(Clone, Debug)]
+pub enum RewardWindow {
+    /// each UTC hour
+    Hour,
+    /// each UTC day
+    Day,
+    /// every N blocks
+    Epoch(u64),
+}
+
+/// accumulates rewards, proposer rewards and commission per validator
+pub struct RewardActor {
+    /// the denom rewards are valued in, eg. uusd
+    pub quote_denom: String,
+    pub window: RewardWindow,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    /// the current window, by operator
+    pub totals: HashMap<String, ValidatorRewardSummary>,
+    /// the last completed window, by operator
+    pub previous: HashMap<String, ValidatorRewardSummary>,
+    /// latest on-chain LUNA exchange rate, by denom
+    pub exchange_rates: HashMap<String, Decimal>,
+}
+impl RewardActor {
+    pub fn create(quote_denom: &str, window: RewardWindow) -> RewardActor {
+        RewardActor {
+            quote_denom: quote_denom.into(),
+            window,
+            from_height: 0,
+            to_height: 0,
+            start: None,
+            end: None,
+            totals: Default::default(),
+            previous: Default::default(),
+            exchange_rates: Default::default(),
+        }
+    }
+
+    /// LUNA price of a denom. exchange rates are the amount of the denom per LUNA
+    fn luna_rate(&self, denom: &str) -> Option<Decimal> {
+        if denom == "uluna" {
+            Some(Decimal::ONE)
+        } else {
+            self.exchange_rates.get(denom).cloned()
+        }
+    }
+
+    /// value of a set of amounts in the quote denom. denoms without a rate are skipped
+    pub fn value(&self, amounts: &HashMap<String, Decimal>) -> Decimal {
+        let quote_rate = match self.luna_rate(&self.quote_denom) {
+            Some(rate) => rate,
+            None => return Decimal::ZERO,
+        };
+        amounts
+            .iter()
+            .fold(Decimal::ZERO, |total, (denom, amount)| {
+                if denom == &self.quote_denom {
+                    total + *amount
+                } else {
+                    match self.luna_rate(denom) {
+                        Some(rate) if !rate.is_zero() => total + amount.div(rate).mul(quote_rate),
+                        _ => {
+                            log::debug!("No exchange rate for {}", denom);
+                            total
+                        }
+                    }
+                }
+            })
+    }
+
+    fn summarize(&self, summary: &ValidatorRewardSummary) -> ValidatorRewardSummary {
+        ValidatorRewardSummary {
+            rewards_value: self.value(&summary.rewards),
+            proposer_rewards_value: self.value(&summary.proposer_rewards),
+            commission_value: self.value(&summary.commission),
+            ..summary.clone()
+        }
+    }
+
+    fn window_closed(&self, height: u64, time: DateTime<Utc>) -> bool {
+        let start = match self.start {
+            Some(start) => start,
+            None => return false,
+        };
+        match self.window {
+            RewardWindow::Hour => time.timestamp() / 3600 != start.timestamp() / 3600,
+            RewardWindow::Day => time.timestamp() / 86400 != start.timestamp() / 86400,
+            RewardWindow::Epoch(blocks) => height >= self.from_height + blocks,
+        }
+    }
+
+    /// note the block an event arrived in, publishing the summary if it starts a new window
+    fn advance(&mut self, height: u64, time: DateTime<Utc>) {
+        if self.window_closed(height, time) {
+            self.publish();
+        }
+        if self.start.is_none() {
+            self.start = Some(time);
+            self.from_height = height;
+        }
+        self.end = Some(time);
+        self.to_height = height;
+    }
+
+    fn publish(&mut self) {
+        let mut validators = self
+            .totals
+            .values()
+            .map(|summary| self.summarize(summary))
+            .collect::<Vec<_>>();
+        validators.sort_by(|a, b| a.operator_address.cmp(&b.operator_address));
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            log::info!(
+                "Reward summary {}-{} for {} validators",
+                self.from_height,
+                self.to_height,
+                validators.len()
+            );
+            Broker::<SystemBroker>::issue_async(MessageRewardSummary {
+                from_height: self.from_height,
+                to_height: self.to_height,
+                start,
+                end,
+                quote_denom: self.quote_denom.clone(),
+                validators: validators.clone(),
+            });
+        }
+        self.previous = validators
+            .into_iter()
+            .map(|summary| (summary.operator_address.clone(), summary))
+            .collect();
+        self.totals = Default::default();
+        self.start = None;
+        self.end = None;
+    }
+
+    fn add(&mut self, operator_address: String, amount: Vec<Coin>, kind: RewardKind) {
+        let summary = self
+            .totals
+            .entry(operator_address.clone())
+            .or_insert_with(|| ValidatorRewardSummary {
+                operator_address,
+                ..Default::default()
+            });
+        let amounts = match kind {
+            RewardKind::Reward => &mut summary.rewards,
+            RewardKind::Proposer => &mut summary.proposer_rewards,
+            RewardKind::Commission => &mut summary.commission,
+        };
+        for coin in amount {
+            *amounts.entry(coin.denom).or_insert(Decimal::ZERO) += coin.amount;
+        }
+    }
+}
+
+enum RewardKind {
+    Reward,
+    Proposer,
+    Commission,
+}
+
+impl Actor for RewardActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockEventReward>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventCommission>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventExchangeRate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for RewardActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Reward Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockEventExchangeRate> for RewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventExchangeRate, _ctx: &mut Self::Context) {
+        self.exchange_rates.insert(msg.denom, msg.exchange_rate);
+    }
+}
+
+impl Handler<MessageBlockEventReward> for RewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventReward, _ctx: &mut Self::Context) {
+        self.advance(msg.height, msg.time);
+        let kind = if msg.is_proposer {
+            RewardKind::Proposer
+        } else {
+            RewardKind::Reward
+        };
+        self.add(msg.validator, msg.amount, kind);
+    }
+}
+
+impl Handler<MessageBlockEventCommission> for RewardActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventCommission, _ctx: &mut Self::Context) {
+        self.advance(msg.height, msg.time);
+        self.add(msg.validator, msg.amount, RewardKind::Commission);
+    }
+}
+
+impl Handler<GetRewardSummary> for RewardActor {
+    type Result = MessageResult<GetRewardSummary>;
+
+    fn handle(&mut self, msg: GetRewardSummary, _ctx: &mut Self::Context) -> Self::Result {
+        if msg.previous {
+            MessageResult(self.previous.get(&msg.operator).cloned())
+        } else {
+            MessageResult(
+                self.totals
+                    .get(&msg.operator)
+                    .map(|summary| self.summarize(summary)),
+            )
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use rust_decimal::prelude::*;
+use terra_rust_api::core_types::Coin;
+use terra_rust_api::Terra;
+
+use crate::messages::{
+    MessageBlockEnd, MessageBlockEventSlash, MessageStakingDelegate, MessageStakingRedelegate,
+    MessageStakingUndelegate, MessageTX, MessageValidator, MessageValidatorStakedDelta,
+    MessageValidatorStakedTotal,
+};
+use crate::types::TxLogEvent;
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// the staking denom
+pub const BOND_DENOM: &str = "uluna";
+
+/// keeps each validator's bonded tokens current from staking events and slashes,
+/// emitting the per-block change & new total at the end of each block.
+/// events are used rather than messages so delegations made through authz are counted
+pub struct StakingActor {
+    /// bonded tokens, by operator
+    pub tokens: HashMap<String, u64>,
+    /// changes in the block being processed, by operator
+    pub pending: HashMap<String, Decimal>,
+    /// operator address, by bech32 consensus address
+    pub consensus_operator: HashMap<String, String>,
+    /// burns of slashes whose operator is not yet known, by bech32 consensus address
+    pub unresolved_burns: HashMap<String, Vec<Coin>>,
+}
+impl StakingActor {
+    /// seed bonded tokens from the LCD
+    pub async fn create(lcd: &str, chain: &str) -> anyhow::Result<StakingActor> {
+        let terra = Terra::lcd_client_no_tx(lcd, chain).await?;
+        let tokens = crate::lcd::validators(&terra, "BOND_STATUS_BONDED")
+            .await?
+            .into_iter()
+            .map(|validator| (validator.operator_address, validator.tokens))
+            .collect();
+        Ok(StakingActor::from_tokens(tokens))
+    }
+
+    pub fn from_tokens(tokens: HashMap<String, u64>) -> StakingActor {
+        StakingActor {
+            tokens,
+            pending: Default::default(),
+            consensus_operator: Default::default(),
+            unresolved_burns: Default::default(),
+        }
+    }
+
+    fn add_delta(&mut self, operator_address: &str, amount: &Coin, increase: bool) {
+        if amount.denom != BOND_DENOM {
+            return;
+        }
+        let delta = self
+            .pending
+            .entry(operator_address.into())
+            .or_insert(Decimal::ZERO);
+        if increase {
+            *delta += amount.amount;
+        } else {
+            *delta -= amount.amount;
+        }
+    }
+
+    /// emit the changes of the block
+    fn flush(&mut self, height: u64) {
+        for (operator_address, token_delta) in self.pending.drain() {
+            let tokens = self.tokens.entry(operator_address.clone()).or_insert(0);
+            let updated = Decimal::from(*tokens) + token_delta;
+            *tokens = if updated.is_sign_negative() {
+                0
+            } else {
+                updated.trunc().to_u64().unwrap_or(*tokens)
+            };
+            log::debug!(
+                "Staked: {} {} {} = {}",
+                height,
+                operator_address,
+                token_delta,
+                tokens
+            );
+            Broker::<SystemBroker>::issue_async(MessageValidatorStakedDelta {
+                height,
+                operator_address: operator_address.clone(),
+                token_delta,
+            });
+            Broker::<SystemBroker>::issue_async(MessageValidatorStakedTotal {
+                height,
+                operator_address,
+                tokens: *tokens,
+            });
+        }
+    }
+}
+impl Actor for StakingActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEnd>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventSlash>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidator>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        // let everyone know the starting weights
+        for (operator_address, tokens) in self.tokens.iter() {
+            Broker::<SystemBroker>::issue_async(MessageValidatorStakedTotal {
+                height: 0,
+                operator_address: operator_address.clone(),
+                tokens: *tokens,
+            });
+        }
+    }
+}
+
+impl Handler<MessageStop> for StakingActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Staking Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockEnd> for StakingActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEnd, _ctx: &mut Self::Context) {
+        self.flush(msg.height);
+    }
+}
+
+impl Handler<MessageValidator> for StakingActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidator, _ctx: &mut Self::Context) {
+        if let Some(tendermint) = &msg.tendermint {
+            self.consensus_operator
+                .insert(tendermint.address.clone(), msg.operator_address.clone());
+            // counted in the block the operator became known
+            if let Some(burned) = self.unresolved_burns.remove(&tendermint.address) {
+                for coin in burned.iter() {
+                    self.add_delta(&msg.operator_address, coin, false);
+                }
+            }
+        }
+    }
+}
+
+impl Handler<MessageBlockEventSlash> for StakingActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventSlash, _ctx: &mut Self::Context) {
+        let operator_address = msg
+            .operator_address
+            .clone()
+            .or_else(|| self.consensus_operator.get(&msg.consensus_address).cloned());
+        match operator_address {
+            Some(operator_address) => {
+                for burned in msg.burned.iter() {
+                    self.add_delta(&operator_address, burned, false);
+                }
+            }
+            None => {
+                log::warn!(
+                    "Slash at {} of unknown validator {}, waiting for its operator",
+                    msg.height,
+                    msg.consensus_address
+                );
+                self.unresolved_burns
+                    .entry(msg.consensus_address)
+                    .or_default()
+                    .extend(msg.burned);
+            }
+        }
+    }
+}
+
+/// staking amounts are logged as coins, or as a bare amount of the bond denom
+fn parse_amount(amount: &str) -> Option<Coin> {
+    match Coin::parse_coins(amount)
+        .ok()
+        .and_then(|coins| coins.into_iter().next())
+    {
+        Some(coin) => Some(coin),
+        None => Decimal::from_str(amount).ok().map(|amount| Coin {
+            denom: BOND_DENOM.into(),
+            amount,
+        }),
+    }
+}
+
+/// the delegators of each staking action of a message, in order.
+/// the staking module's message event carries the delegator as the sender
+fn staking_senders(events: &[TxLogEvent], msg_index: usize) -> Vec<String> {
+    let mut senders = vec![];
+    for event in events
+        .iter()
+        .filter(|event| event.msg_index == msg_index && event.s_type == "message")
+    {
+        let mut staking = false;
+        for (key, value) in event.attributes.iter() {
+            if key == "module" {
+                staking = value == "staking";
+            } else if key == "sender" && staking {
+                senders.push(value.clone());
+                staking = false;
+            }
+        }
+    }
+    senders
+}
+
+impl Handler<MessageTX> for StakingActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        if msg.tx.is_failed() {
+            return;
+        }
+        let height = msg.tx.height;
+        let txhash = msg.tx.txhash.clone();
+        let signer = msg.tx.signers().into_iter().next().unwrap_or_default();
+        let events = msg.tx.log_events();
+        let mut actions_seen: HashMap<usize, usize> = HashMap::new();
+        for event in events.iter() {
+            let first_key = match event.s_type.as_str() {
+                "create_validator" | "delegate" | "unbond" => "validator",
+                "redelegate" => "source_validator",
+                _ => continue,
+            };
+            let senders = staking_senders(&events, event.msg_index);
+            for action in event.actions(first_key) {
+                let seen = actions_seen.entry(event.msg_index).or_insert(0);
+                let delegator_address = action
+                    .get("delegator")
+                    .or_else(|| senders.get(*seen))
+                    .cloned()
+                    .unwrap_or_else(|| signer.clone());
+                *seen += 1;
+                let amount = match action.get("amount").and_then(|a| parse_amount(a)) {
+                    Some(amount) => amount,
+                    None => {
+                        log::error!("Expected amount: {} {} {:?}", txhash, event.s_type, action);
+                        continue;
+                    }
+                };
+                match event.s_type.as_str() {
+                    "create_validator" | "delegate" => {
+                        let validator_address = action[first_key].clone();
+                        self.add_delta(&validator_address, &amount, true);
+                        Broker::<SystemBroker>::issue_async(MessageStakingDelegate {
+                            height,
+                            txhash: txhash.clone(),
+                            delegator_address,
+                            validator_address,
+                            amount,
+                        });
+                    }
+                    "unbond" => {
+                        let validator_address = action[first_key].clone();
+                        self.add_delta(&validator_address, &amount, false);
+                        Broker::<SystemBroker>::issue_async(MessageStakingUndelegate {
+                            height,
+                            txhash: txhash.clone(),
+                            delegator_address,
+                            validator_address,
+                            amount,
+                        });
+                    }
+                    _ => {
+                        let validator_src_address = action[first_key].clone();
+                        let validator_dst_address =
+                            match action.get("destination_validator").cloned() {
+                                Some(dst) => dst,
+                                None => {
+                                    log::error!(
+                                        "Expected destination validator: {} {:?}",
+                                        txhash,
+                                        action
+                                    );
+                                    continue;
+                                }
+                            };
+                        self.add_delta(&validator_src_address, &amount, false);
+                        self.add_delta(&validator_dst_address, &amount, true);
+                        Broker::<SystemBroker>::issue_async(MessageStakingRedelegate {
+                            height,
+                            txhash: txhash.clone(),
+                            delegator_address,
+                            validator_src_address,
+                            validator_dst_address,
+                            amount,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::ops::{Div, Mul};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use terra_rust_api::core_types::Coin;
+
+use crate::messages::{
+    MessageBlockEventExchangeRate, MessageBlockProposed, MessageSwap, MessageSwapSummary,
+    MessageTX, SwapPairVolume,
+};
+use crate::types::{MsgSwap, MsgSwapSend};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// blocks in a swap period, unless told otherwise
+pub const DEFAULT_SWAP_PERIOD: u64 = 600;
+
+/// a pair's volume, along with what is needed to average its spread
+struct SwapTally {
+    volume: SwapPairVolume,
+    /// offered amount of the swaps with a spread
+    spread_offer: Decimal,
+    /// sum of spread * offered amount
+    spread_sum: Decimal,
+}
+
+/// decodes market swaps, and totals their volume & spread vs. the oracle rate per denom pair
+pub struct SwapActor {
+    /// the latest oracle exchange rate, by denom. rates are set at the end of a block,
+    /// after its swaps, so these are the rates the block's swaps were made at.
+    /// they are the same points ExchangeRateActor keeps, held here so a swap is priced
+    /// as it is tallied rather than after an async query that could land in a later period
+    pub exchange_rates: HashMap<String, Decimal>,
+    /// blocks in a period
+    pub period: u64,
+    pub period_start_height: u64,
+    pub last_height: u64,
+    tallies: HashMap<(String, String), SwapTally>,
+}
+impl SwapActor {
+    pub fn create(period: u64) -> SwapActor {
+        SwapActor {
+            exchange_rates: Default::default(),
+            period: period.max(1),
+            period_start_height: 0,
+            last_height: 0,
+            tallies: Default::default(),
+        }
+    }
+
+    fn tally(&mut self, offer_denom: &str, ask_denom: &str) -> &mut SwapTally {
+        self.tallies
+            .entry((offer_denom.into(), ask_denom.into()))
+            .or_insert_with(|| SwapTally {
+                volume: SwapPairVolume {
+                    offer_denom: offer_denom.into(),
+                    ask_denom: ask_denom.into(),
+                    swaps: 0,
+                    offer_amount: Decimal::ZERO,
+                    ask_amount: Decimal::ZERO,
+                    fees: Decimal::ZERO,
+                    spread: None,
+                },
+                spread_offer: Decimal::ZERO,
+                spread_sum: Decimal::ZERO,
+            })
+    }
+
+    /// LUNA price of a denom. exchange rates are the amount of the denom per LUNA
+    fn luna_rate(&self, denom: &str) -> Option<Decimal> {
+        if denom == "uluna" {
+            Some(Decimal::ONE)
+        } else {
+            self.exchange_rates.get(denom).cloned()
+        }
+    }
+
+    fn record(&mut self, swap: &MessageSwap) {
+        if self.period_start_height == 0 {
+            self.period_start_height = swap.height;
+        }
+        let tally = self.tally(&swap.offer.denom, &swap.ask_denom);
+        tally.volume.swaps += 1;
+        tally.volume.offer_amount += swap.offer.amount;
+        if let Some(swap_coin) = &swap.swap_coin {
+            tally.volume.ask_amount += swap_coin.amount;
+        }
+        if let Some(swap_fee) = &swap.swap_fee {
+            tally.volume.fees += swap_fee.amount;
+        }
+        let swap_coin = match &swap.swap_coin {
+            Some(swap_coin) if !swap.offer.amount.is_zero() => swap_coin,
+            _ => return,
+        };
+        match (
+            self.luna_rate(&swap.offer.denom),
+            self.luna_rate(&swap_coin.denom),
+        ) {
+            (Some(offer_rate), Some(ask_rate)) if !offer_rate.is_zero() => {
+                let oracle_ask = swap.offer.amount.mul(ask_rate).div(offer_rate);
+                if oracle_ask.is_zero() {
+                    return;
+                }
+                let spread = Decimal::ONE - swap_coin.amount.div(oracle_ask);
+                self.add_spread(&swap.offer, swap_coin, spread);
+            }
+            _ => log::debug!(
+                "No exchange rate for swap {} -> {}",
+                swap.offer.denom,
+                swap_coin.denom
+            ),
+        }
+    }
+
+    fn add_spread(&mut self, offer: &Coin, swap_coin: &Coin, spread: Decimal) {
+        let tally = self.tally(&offer.denom, &swap_coin.denom);
+        tally.spread_offer += offer.amount;
+        tally.spread_sum += spread.mul(offer.amount);
+    }
+
+    fn flush(&mut self) {
+        if self.tallies.is_empty() {
+            self.period_start_height = self.last_height + 1;
+            return;
+        }
+        let mut pairs: Vec<SwapPairVolume> = self
+            .tallies
+            .drain()
+            .map(|(_, tally)| {
+                let mut volume = tally.volume;
+                if !tally.spread_offer.is_zero() {
+                    volume.spread = Some(tally.spread_sum.div(tally.spread_offer));
+                }
+                volume
+            })
+            .collect();
+        pairs.sort_by(|a, b| (&a.offer_denom, &a.ask_denom).cmp(&(&b.offer_denom, &b.ask_denom)));
+        log::debug!(
+            "Swaps: {}-{} {} pairs",
+            self.period_start_height,
+            self.last_height,
+            pairs.len()
+        );
+        Broker::<SystemBroker>::issue_async(MessageSwapSummary {
+            from_height: self.period_start_height,
+            to_height: self.last_height,
+            pairs,
+        });
+        self.period_start_height = self.last_height + 1;
+    }
+}
+impl Actor for SwapActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventExchangeRate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for SwapActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Swap Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockProposed> for SwapActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        if self.period_start_height == 0 {
+            self.period_start_height = msg.height;
+        }
+        if msg.height >= self.period_start_height + self.period {
+            self.flush();
+        }
+        self.last_height = msg.height;
+    }
+}
+
+impl Handler<MessageBlockEventExchangeRate> for SwapActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventExchangeRate, _ctx: &mut Self::Context) {
+        self.exchange_rates.insert(msg.denom, msg.exchange_rate);
+    }
+}
+
+impl Handler<MessageTX> for SwapActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        if msg.tx.is_failed() {
+            return;
+        }
+        let events = msg.tx.log_events();
+        for (msg_index, m) in msg.tx.tx.body.messages.iter().enumerate() {
+            let (trader, recipient, offer, ask_denom) = match m.get("@type").and_then(Value::as_str)
+            {
+                Some("/terra.market.v1beta1.MsgSwap") => {
+                    match serde_json::from_value::<MsgSwap>(m.clone()) {
+                        Ok(swap) => (
+                            swap.trader.clone(),
+                            swap.trader,
+                            swap.offer_coin,
+                            swap.ask_denom,
+                        ),
+                        Err(e) => {
+                            log::error!("Expected swap: {} - {}", e, m.to_string());
+                            continue;
+                        }
+                    }
+                }
+                Some("/terra.market.v1beta1.MsgSwapSend") => {
+                    match serde_json::from_value::<MsgSwapSend>(m.clone()) {
+                        Ok(swap) => (
+                            swap.from_address,
+                            swap.to_address,
+                            swap.offer_coin,
+                            swap.ask_denom,
+                        ),
+                        Err(e) => {
+                            log::error!("Expected swap send: {} - {}", e, m.to_string());
+                            continue;
+                        }
+                    }
+                }
+                _ => continue,
+            };
+            let swap_event = events
+                .iter()
+                .find(|e| e.msg_index == msg_index && e.s_type == "swap");
+            let event_coin = |key: &str| {
+                swap_event
+                    .and_then(|e| e.attribute(key))
+                    .and_then(|coin| Coin::parse_coins(coin).ok())
+                    .and_then(|coins| coins.into_iter().next())
+            };
+            let swap = MessageSwap {
+                height: msg.tx.height,
+                time: msg.tx.timestamp,
+                txhash: msg.tx.txhash.clone(),
+                trader,
+                recipient,
+                offer,
+                ask_denom,
+                swap_coin: event_coin("swap_coin"),
+                swap_fee: event_coin("swap_fee"),
+            };
+            self.record(&swap);
+            Broker::<SystemBroker>::issue_async(swap);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use serde_json::Value;
+
+use crate::messages::{
+    MessageFeederDelegated, MessageTX, MessageTxFailed, MessageValidatorEvent, TxErrorCategory,
+    ValidatorEventType,
+};
+use crate::types::TXandResult;
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// classify a failure from its codespace/code (cosmos-sdk error registry), falling back to the log.
+/// oracle failures are only recognised by the oracle module, as gov votes fail too
+pub fn classify_failure(tx: &TXandResult) -> TxErrorCategory {
+    match (tx.codespace.as_deref(), tx.code) {
+        (Some("sdk"), Some(11)) => return TxErrorCategory::OutOfGas,
+        (Some("sdk"), Some(13)) => return TxErrorCategory::InsufficientFee,
+        (Some("sdk"), Some(32)) => return TxErrorCategory::SequenceMismatch,
+        (Some("oracle"), _) => return TxErrorCategory::OracleVote,
+        (Some("wasm"), _) => return TxErrorCategory::Contract,
+        _ => {}
+    }
+    let raw_log = tx.raw_log.to_lowercase();
+    if raw_log.contains("out of gas") {
+        TxErrorCategory::OutOfGas
+    } else if raw_log.contains("insufficient fee") {
+        TxErrorCategory::InsufficientFee
+    } else if raw_log.contains("sequence mismatch")
+        || raw_log.contains("incorrect account sequence")
+    {
+        TxErrorCategory::SequenceMismatch
+    } else if raw_log.contains("contract") || raw_log.contains("wasm") {
+        TxErrorCategory::Contract
+    } else if tx.tx.body.messages.iter().any(|m| {
+        m.get("@type")
+            .and_then(Value::as_str)
+            .map(|msg_type| msg_type.starts_with("/terra.oracle."))
+            .unwrap_or(false)
+    }) {
+        TxErrorCategory::OracleVote
+    } else {
+        TxErrorCategory::Other
+    }
+}
+
+/// detects failed txs and classifies why they failed.
+/// failures signed by one of our accounts are also raised as validator events
+pub struct TxFailureActor {
+    /// our accounts (operator, feeder ...) by address, with the operator they belong to
+    pub accounts: HashMap<String, String>,
+    /// operators whose delegated feeders are added to `accounts` as they change
+    pub operators: Vec<String>,
+    /// the delegated feeder added to `accounts`, by operator. configured accounts are not
+    /// listed, so they stay when the feeder changes
+    pub feeders: HashMap<String, String>,
+}
+impl TxFailureActor {
+    pub fn create(accounts: HashMap<String, String>, operators: Vec<String>) -> TxFailureActor {
+        TxFailureActor {
+            accounts,
+            operators,
+            feeders: Default::default(),
+        }
+    }
+}
+impl Actor for TxFailureActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageFeederDelegated>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for TxFailureActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("TxFailure Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageFeederDelegated> for TxFailureActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageFeederDelegated, _ctx: &mut Self::Context) {
+        if !self.operators.contains(&msg.operator_address) {
+            return;
+        }
+        if let Some(previous) = self.feeders.remove(&msg.operator_address) {
+            self.accounts.remove(&previous);
+        }
+        if !self.accounts.contains_key(&msg.feeder) {
+            self.accounts
+                .insert(msg.feeder.clone(), msg.operator_address.clone());
+            self.feeders.insert(msg.operator_address, msg.feeder);
+        }
+    }
+}
+
+impl Handler<MessageTX> for TxFailureActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        let tx = msg.tx;
+        if !tx.is_failed() {
+            return;
+        }
+        let category = classify_failure(&tx);
+        let signers = tx.signers();
+        let msg_types: Vec<String> = tx
+            .tx
+            .body
+            .messages
+            .iter()
+            .filter_map(|m| m.get("@type").and_then(Value::as_str).map(String::from))
+            .collect();
+        log::debug!(
+            "TX Failed: {} {} {:?} {}",
+            tx.height,
+            tx.txhash,
+            category,
+            tx.raw_log
+        );
+        for signer in signers.iter() {
+            if let Some(operator_address) = self.accounts.get(signer) {
+                Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+                    height: tx.height,
+                    operator_address: operator_address.clone(),
+                    moniker: None,
+                    event_type: ValidatorEventType::WARN,
+                    message: format!("TX from {} failed ({:?}): {}", signer, category, tx.raw_log),
+                    hash: Some(tx.txhash.clone()),
+                });
+            }
+        }
+        Broker::<SystemBroker>::issue_async(MessageTxFailed {
+            height: tx.height,
+            txhash: tx.txhash,
+            signers,
+            msg_types,
+            category,
+            code: tx.code,
+            codespace: tx.codespace,
+            raw_log: tx.raw_log,
+            gas_wanted: tx.gas_wanted,
+            gas_used: tx.gas_used,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn failed_tx(codespace: Option<&str>, code: u32, raw_log: &str, msg_type: &str) -> TXandResult {
+        serde_json::from_value(json!({
+            "height": "5000000",
+            "txhash": "ABCDEF",
+            "raw_log": raw_log,
+            "logs": [],
+            "gas_wanted": "200000",
+            "gas_used": "180000",
+            "tx": {
+                "@type": "/cosmos.tx.v1beta1.Tx",
+                "body": {
+                    "messages": [{ "@type": msg_type }],
+                    "memo": ""
+                }
+            },
+            "timestamp": "2021-10-18T10:00:00Z",
+            "code": code,
+            "codespace": codespace,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn classify_by_codespace() {
+        let send = "/cosmos.bank.v1beta1.MsgSend";
+        assert_eq!(
+            classify_failure(&failed_tx(Some("sdk"), 11, "out of gas", send)),
+            TxErrorCategory::OutOfGas
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(Some("sdk"), 13, "", send)),
+            TxErrorCategory::InsufficientFee
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(Some("sdk"), 32, "", send)),
+            TxErrorCategory::SequenceMismatch
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(Some("oracle"), 2, "", send)),
+            TxErrorCategory::OracleVote
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(Some("wasm"), 4, "", send)),
+            TxErrorCategory::Contract
+        );
+    }
+
+    #[test]
+    fn classify_by_log() {
+        let send = "/cosmos.bank.v1beta1.MsgSend";
+        assert_eq!(
+            classify_failure(&failed_tx(None, 1, "Out of gas in location", send)),
+            TxErrorCategory::OutOfGas
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(
+                None,
+                1,
+                "account sequence mismatch, expected 10, got 9: incorrect account sequence",
+                send
+            )),
+            TxErrorCategory::SequenceMismatch
+        );
+        assert_eq!(
+            classify_failure(&failed_tx(None, 1, "something else", send)),
+            TxErrorCategory::Other
+        );
+    }
+
+    #[test]
+    fn gov_vote_is_not_an_oracle_failure() {
+        let tx = failed_tx(
+            Some("gov"),
+            2,
+            "failed to execute message; message index: 0: inactive proposal: vote failed",
+            "/cosmos.gov.v1beta1.MsgVote",
+        );
+        assert_eq!(classify_failure(&tx), TxErrorCategory::Other);
+        let tx = failed_tx(
+            None,
+            1,
+            "no aggregate prevote",
+            "/terra.oracle.v1beta1.MsgAggregateExchangeRateVote",
+        );
+        assert_eq!(classify_failure(&tx), TxErrorCategory::OracleVote);
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use terra_rust_api::{staking_types, tendermint_types, Terra};
+
+use crate::address::bech32_to_hex;
+use crate::messages::{
+    AbsentSigner, MessageBlockAbsentSigners, MessageBlockEventJail, MessageBlockEventSlash,
+    MessageBlockProposed, MessageBlockSignatures, MessageStakingDelegate, MessageStakingRedelegate,
+    MessageStakingUndelegate, MessageValidator, MessageValidatorEvent, MessageValidatorRemoved,
+    ValidatorEventType,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// how often every validator is re-read from the LCD
+pub const DEFAULT_VALIDATOR_REFRESH: Duration = Duration::from_secs(600);
+
+/// every bond status, as the LCD only lists bonded validators unless asked for
+const BOND_STATUSES: [&str; 3] = [
+    "BOND_STATUS_BONDED",
+    "BOND_STATUS_UNBONDING",
+    "BOND_STATUS_UNBONDED",
+];
+
+/// staking validators & the tendermint validator set
+type RefreshResult = anyhow::Result<(
+    Vec<staking_types::Validator>,
+    Vec<tendermint_types::Validator>,
+)>;
+
+/// every validator, whatever its bond status
+async fn all_validators(terra: &Terra) -> anyhow::Result<Vec<staking_types::Validator>> {
+    let mut validators = vec![];
+    for status in BOND_STATUSES.iter() {
+        validators.extend(crate::lcd::validators(terra, status).await?);
+    }
+    Ok(validators)
+}
+
+/// periodically re-reads the validators from the LCD, sending a MessageValidator for each.
+/// as it knows the validator set, it also raises the CRITICAL events for slashes & jailings
+/// and names the validators absent from each commit
+pub struct ValidatorActor {
+    pub lcd: String,
+    pub chain: String,
+    pub refresh: Duration,
+    pub last_height: u64,
+    /// validators being re-read after a staking/slashing event
+    pub refreshing: HashSet<String>,
+    /// the tendermint validator set, by consensus public key
+    pub tendermint: HashMap<String, tendermint_types::Validator>,
+    /// operator address by consensus address (hex). kept across refreshes, so validators
+    /// which have left the set can still be named when they are slashed or jailed
+    pub consensus_operator: HashMap<String, String>,
+    /// moniker, by operator
+    pub monikers: HashMap<String, String>,
+    /// operators returned by the last full refresh
+    pub known: HashSet<String>,
+    /// consensus addresses (hex) in validator set order, as signatures appear in commits
+    pub validator_set: Vec<String>,
+    /// a full refresh is in progress
+    pub refreshing_all: bool,
+    /// slashes & jailings of unknown validators, paged once a full refresh names them.
+    /// (height, consensus address, message)
+    pub unresolved: Vec<(u64, String, String)>,
+}
+impl ValidatorActor {
+    pub fn create(lcd: &str, chain: &str) -> ValidatorActor {
+        ValidatorActor {
+            lcd: lcd.into(),
+            chain: chain.into(),
+            refresh: DEFAULT_VALIDATOR_REFRESH,
+            last_height: 0,
+            refreshing: Default::default(),
+            tendermint: Default::default(),
+            consensus_operator: Default::default(),
+            monikers: Default::default(),
+            known: Default::default(),
+            validator_set: vec![],
+            refreshing_all: false,
+            unresolved: vec![],
+        }
+    }
+
+    fn emit(&mut self, validator: staking_types::Validator) {
+        let tendermint = self
+            .tendermint
+            .get(&validator.consensus_pubkey.key)
+            .cloned();
+        if let Some(hex) = tendermint.as_ref().and_then(|v| bech32_to_hex(&v.address)) {
+            self.consensus_operator
+                .insert(hex, validator.operator_address.clone());
+        }
+        self.monikers.insert(
+            validator.operator_address.clone(),
+            validator.description.moniker.clone(),
+        );
+        Broker::<SystemBroker>::issue_async(MessageValidator {
+            height: self.last_height,
+            operator_address: validator.operator_address.clone(),
+            validator,
+            tendermint,
+        });
+    }
+
+    fn refresh_all(&mut self, ctx: &mut Context<Self>) {
+        if self.refreshing_all {
+            return;
+        }
+        self.refreshing_all = true;
+        let lcd = self.lcd.clone();
+        let chain = self.chain.clone();
+        let fut = async move {
+            let terra = Terra::lcd_client_no_tx(&lcd, &chain).await?;
+            let validators = all_validators(&terra).await?;
+            let tendermint = crate::lcd::validator_set(&terra).await?;
+            Ok::<_, anyhow::Error>((validators, tendermint))
+        };
+        ctx.spawn(
+            fut.into_actor(self)
+                .map(|result: RefreshResult, act, _ctx| {
+                    act.refreshing_all = false;
+                    act.apply_refresh(result)
+                }),
+        );
+    }
+
+    fn apply_refresh(&mut self, result: RefreshResult) {
+        match result {
+            Ok((validators, tendermint)) => {
+                // by voting power, then address, which is the order of the signatures in commits
+                let mut validator_set = tendermint
+                    .iter()
+                    .filter_map(|v| bech32_to_hex(&v.address).map(|hex| (v.voting_power, hex)))
+                    .collect::<Vec<_>>();
+                validator_set.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+                self.validator_set = validator_set.into_iter().map(|(_, hex)| hex).collect();
+                self.tendermint = tendermint
+                    .into_iter()
+                    .map(|v| (v.pub_key.key.clone(), v))
+                    .collect();
+                log::info!(
+                    "Refreshed {} validators, {} in validator set",
+                    validators.len(),
+                    self.tendermint.len()
+                );
+                let known: HashSet<String> = validators
+                    .iter()
+                    .map(|validator| validator.operator_address.clone())
+                    .collect();
+                for operator_address in self.known.difference(&known) {
+                    log::info!("Validator {} removed", operator_address);
+                    Broker::<SystemBroker>::issue_async(MessageValidatorRemoved {
+                        height: self.last_height,
+                        operator_address: operator_address.clone(),
+                    });
+                }
+                self.known = known;
+                for validator in validators {
+                    self.emit(validator);
+                }
+                for (height, consensus_address, message) in std::mem::take(&mut self.unresolved) {
+                    match self.consensus_operator(&consensus_address) {
+                        Some(operator_address) => self.page_slashing(
+                            height,
+                            &consensus_address,
+                            operator_address,
+                            message,
+                        ),
+                        None => log::error!(
+                            "Validator {} still unknown, not paging: {}",
+                            consensus_address,
+                            message
+                        ),
+                    }
+                }
+            }
+            Err(e) => log::error!("Unable to refresh validators: {}", e),
+        }
+    }
+
+    /// re-read one validator now, unless it is already being re-read
+    fn refresh_validator(&mut self, operator_address: &str, ctx: &mut Context<Self>) {
+        if !self.refreshing.insert(operator_address.into()) {
+            return;
+        }
+        let lcd = self.lcd.clone();
+        let chain = self.chain.clone();
+        let queried = operator_address.to_string();
+        let fut = async move {
+            let terra = Terra::lcd_client_no_tx(&lcd, &chain).await?;
+            let validator = terra.staking().validator(&queried).await?.result;
+            Ok::<_, anyhow::Error>(validator)
+        };
+        let operator_address = operator_address.to_string();
+        ctx.spawn(fut.into_actor(self).map(move |result, act, _ctx| {
+            act.refreshing.remove(&operator_address);
+            match result {
+                Ok(validator) => act.emit(validator),
+                Err(e) => log::error!("Unable to refresh validator {}: {}", operator_address, e),
+            }
+        }));
+    }
+
+    /// operator of a consensus address, either bech32 (from events) or hex (from blocks)
+    fn consensus_operator(&self, consensus_address: &str) -> Option<String> {
+        let hex =
+            bech32_to_hex(consensus_address).unwrap_or_else(|| consensus_address.to_uppercase());
+        self.consensus_operator.get(&hex).cloned()
+    }
+
+    fn page_slashing(
+        &self,
+        height: u64,
+        consensus_address: &str,
+        operator_address: String,
+        message: String,
+    ) {
+        Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+            height,
+            moniker: self.monikers.get(&operator_address).cloned(),
+            operator_address,
+            event_type: ValidatorEventType::CRITICAL,
+            message: format!("{} ({})", message, consensus_address),
+            hash: None,
+        });
+    }
+
+    /// page about a slash or jail, and re-read the validator.
+    /// an unknown validator is paged once a full refresh has named it
+    fn slashing_event(
+        &mut self,
+        height: u64,
+        consensus_address: &str,
+        operator_address: Option<String>,
+        message: String,
+        ctx: &mut Context<Self>,
+    ) {
+        match operator_address.or_else(|| self.consensus_operator(consensus_address)) {
+            Some(operator_address) => {
+                self.page_slashing(height, consensus_address, operator_address.clone(), message);
+                self.refresh_validator(&operator_address, ctx);
+            }
+            None => {
+                log::warn!(
+                    "Validator {} unknown, refreshing all validators",
+                    consensus_address
+                );
+                self.unresolved
+                    .push((height, consensus_address.into(), message));
+                self.refresh_all(ctx);
+            }
+        }
+    }
+}
+impl Actor for ValidatorActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockSignatures>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventSlash>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventJail>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStakingDelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStakingUndelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStakingRedelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+        self.refresh_all(ctx);
+        ctx.run_interval(self.refresh, |act, ctx| act.refresh_all(ctx));
+    }
+}
+
+impl Handler<MessageStop> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Validator Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageBlockProposed> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        self.last_height = msg.height;
+    }
+}
+
+/// name the absent signers, from their position in the validator set
+impl Handler<MessageBlockSignatures> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockSignatures, ctx: &mut Self::Context) {
+        if msg.absent_slots.is_empty() {
+            return;
+        }
+        let set = &self.validator_set;
+        let current = msg.slots == set.len()
+            && msg
+                .signed
+                .iter()
+                .chain(msg.nil.iter())
+                .all(|signer| set.get(signer.slot) == Some(&signer.address));
+        if !current {
+            log::warn!(
+                "Validator set does not match signatures at {}, refreshing",
+                msg.height
+            );
+            self.refresh_all(ctx);
+            return;
+        }
+        let absent = msg
+            .absent_slots
+            .iter()
+            .map(|slot| {
+                let consensus_address = self.validator_set[*slot].clone();
+                let operator_address = self.consensus_operator.get(&consensus_address).cloned();
+                AbsentSigner {
+                    slot: *slot,
+                    moniker: operator_address
+                        .as_ref()
+                        .and_then(|operator_address| self.monikers.get(operator_address))
+                        .cloned(),
+                    consensus_address,
+                    operator_address,
+                }
+            })
+            .collect();
+        Broker::<SystemBroker>::issue_async(MessageBlockAbsentSigners {
+            height: msg.height,
+            absent,
+        });
+    }
+}
+
+impl Handler<MessageBlockEventSlash> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventSlash, ctx: &mut Self::Context) {
+        self.slashing_event(
+            msg.height,
+            &msg.consensus_address,
+            msg.operator_address.clone(),
+            format!(
+                "Validator slashed. reason:{} jailed:{}",
+                msg.reason, msg.jailed
+            ),
+            ctx,
+        );
+    }
+}
+
+impl Handler<MessageBlockEventJail> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventJail, ctx: &mut Self::Context) {
+        self.slashing_event(
+            msg.height,
+            &msg.consensus_address,
+            msg.operator_address.clone(),
+            "Validator jailed".into(),
+            ctx,
+        );
+    }
+}
+
+impl Handler<MessageStakingDelegate> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingDelegate, ctx: &mut Self::Context) {
+        self.refresh_validator(&msg.validator_address, ctx);
+    }
+}
+
+impl Handler<MessageStakingUndelegate> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingUndelegate, ctx: &mut Self::Context) {
+        self.refresh_validator(&msg.validator_address, ctx);
+    }
+}
+
+impl Handler<MessageStakingRedelegate> for ValidatorActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingRedelegate, ctx: &mut Self::Context) {
+        self.refresh_validator(&msg.validator_src_address, ctx);
+        self.refresh_validator(&msg.validator_dst_address, ctx);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use serde_json::Value;
+
+use crate::messages::{
+    MessageTX, MessageWasmEvent, MessageWasmExecute, MessageWasmInstantiate, SubscribeWasmContract,
+};
+use crate::types::{contract_attributes, decode_contract_msg, BlockMsg, MsgInstantiateContract};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// decodes contract executions, instantiations & their events. everything is broadcast, and
+/// also sent to the recipients subscribed to the contract
+#[derive(Default)]
+pub struct WasmActor {
+    /// event recipients, by contract address
+    pub event_subscribers: HashMap<String, Vec<Recipient<MessageWasmEvent>>>,
+    /// execute recipients, by contract address
+    pub execute_subscribers: HashMap<String, Vec<Recipient<MessageWasmExecute>>>,
+}
+impl WasmActor {
+    pub fn create() -> WasmActor {
+        WasmActor::default()
+    }
+
+    fn forward_event(&mut self, msg: &MessageWasmEvent) {
+        if let Some(recipients) = self.event_subscribers.get_mut(&msg.contract_address) {
+            recipients.retain(|recipient| recipient.connected());
+            for recipient in recipients.iter() {
+                if let Err(e) = recipient.do_send(msg.clone()) {
+                    log::warn!(
+                        "Unable to forward wasm event {}: {}",
+                        msg.contract_address,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    fn forward_execute(&mut self, msg: &MessageWasmExecute) {
+        if let Some(recipients) = self.execute_subscribers.get_mut(&msg.contract) {
+            recipients.retain(|recipient| recipient.connected());
+            for recipient in recipients.iter() {
+                if let Err(e) = recipient.do_send(msg.clone()) {
+                    log::warn!("Unable to forward wasm execute {}: {}", msg.contract, e);
+                }
+            }
+        }
+    }
+}
+impl Actor for WasmActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageWasmEvent>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for WasmActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Wasm Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<SubscribeWasmContract> for WasmActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SubscribeWasmContract, _ctx: &mut Self::Context) {
+        self.event_subscribers
+            .entry(msg.contract_address.clone())
+            .or_insert_with(Vec::new)
+            .push(msg.events);
+        if let Some(executes) = msg.executes {
+            self.execute_subscribers
+                .entry(msg.contract_address)
+                .or_insert_with(Vec::new)
+                .push(executes);
+        }
+    }
+}
+
+/// begin/end block events come from the intake. tx events are forwarded as they are decoded
+impl Handler<MessageWasmEvent> for WasmActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageWasmEvent, _ctx: &mut Self::Context) {
+        if msg.txhash.is_none() {
+            self.forward_event(&msg);
+        }
+    }
+}
+
+impl Handler<MessageTX> for WasmActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        if msg.tx.is_failed() {
+            return;
+        }
+        let height = msg.tx.height;
+        let txhash = msg.tx.txhash.clone();
+        let events = msg.tx.log_events();
+        for (msg_index, m) in msg.tx.tx.body.messages.iter().enumerate() {
+            match m.get("@type").and_then(Value::as_str) {
+                Some("/terra.wasm.v1beta1.MsgExecuteContract") => {
+                    match serde_json::from_value::<BlockMsg>(m.clone()) {
+                        Ok(execute) => {
+                            let execute = MessageWasmExecute {
+                                height,
+                                txhash: txhash.clone(),
+                                sender: execute.sender,
+                                contract: execute.contract,
+                                execute_msg: execute
+                                    .execute_msg
+                                    .map(decode_contract_msg)
+                                    .unwrap_or_default(),
+                                coins: execute.coins,
+                            };
+                            self.forward_execute(&execute);
+                            Broker::<SystemBroker>::issue_async(execute);
+                        }
+                        Err(e) => {
+                            log::error!("Expected execute contract: {} - {}", e, m.to_string())
+                        }
+                    }
+                }
+                Some("/terra.wasm.v1beta1.MsgInstantiateContract") => {
+                    match serde_json::from_value::<MsgInstantiateContract>(m.clone()) {
+                        Ok(instantiate) => {
+                            let contract_address = events
+                                .iter()
+                                .filter(|e| {
+                                    e.msg_index == msg_index && e.s_type == "instantiate_contract"
+                                })
+                                .find_map(|e| e.attribute("contract_address"))
+                                .map(String::from);
+                            log::info!(
+                                "Contract instantiated: {} code {} {}",
+                                height,
+                                instantiate.code_id,
+                                contract_address.clone().unwrap_or_default()
+                            );
+                            Broker::<SystemBroker>::issue_async(MessageWasmInstantiate {
+                                height,
+                                txhash: txhash.clone(),
+                                sender: instantiate.sender,
+                                admin: instantiate.admin.filter(|admin| !admin.is_empty()),
+                                code_id: instantiate.code_id,
+                                contract_address,
+                                init_msg: decode_contract_msg(instantiate.init_msg),
+                                init_coins: instantiate.init_coins,
+                            });
+                        }
+                        Err(e) => {
+                            log::error!("Expected instantiate contract: {} - {}", e, m.to_string())
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for event in events
+            .iter()
+            .filter(|e| e.s_type == "wasm" || e.s_type == "from_contract")
+        {
+            for (contract_address, attributes) in contract_attributes(&event.attributes) {
+                let wasm_event = MessageWasmEvent {
+                    height,
+                    txhash: Some(txhash.clone()),
+                    event_type: event.s_type.clone(),
+                    contract_address,
+                    action: attributes
+                        .iter()
+                        .find(|(key, _)| key == "action")
+                        .map(|(_, value)| value.clone()),
+                    attributes,
+                };
+                self.forward_event(&wasm_event);
+                Broker::<SystemBroker>::issue_async(wasm_event);
+            }
+        }
+    }
+}
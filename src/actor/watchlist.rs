@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, HashMap};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use serde_json::Value;
+
+use crate::messages::{
+    AddressRole, MessageAddressActivity, MessageTX, MessageValidatorEvent, ValidatorEventType,
+};
+use crate::types::{TXandResult, SIGNER_FIELDS};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// message fields holding a recipient
+const RECIPIENT_FIELDS: &[&str] = &["to_address", "receiver", "recipient"];
+/// message fields holding a contract
+const CONTRACT_FIELDS: &[&str] = &["contract", "contract_address"];
+
+/// an address to watch
+#[derive(Clone, Debug)]
+pub struct WatchedAddress {
+    pub label: String,
+    /// roles to report. empty reports all of them
+    pub roles: Vec<AddressRole>,
+    pub severity: ValidatorEventType,
+    /// the operator events are raised against. defaults to the address itself
+    pub operator_address: Option<String>,
+}
+impl WatchedAddress {
+    pub fn new(label: &str, severity: ValidatorEventType) -> WatchedAddress {
+        WatchedAddress {
+            label: label.into(),
+            roles: vec![],
+            severity,
+            operator_address: None,
+        }
+    }
+    fn reports(&self, role: AddressRole) -> bool {
+        self.roles.is_empty() || self.roles.contains(&role)
+    }
+}
+
+/// reports any tx involving a watched address
+pub struct WatchlistActor {
+    pub watched: HashMap<String, WatchedAddress>,
+}
+impl WatchlistActor {
+    pub fn create(watched: HashMap<String, WatchedAddress>) -> WatchlistActor {
+        WatchlistActor { watched }
+    }
+
+    /// watched addresses in the tx, with the first message type they appear in
+    fn scan(&self, tx: &TXandResult) -> BTreeMap<(String, AddressRole), Option<String>> {
+        let mut found: BTreeMap<(String, AddressRole), Option<String>> = BTreeMap::new();
+        let mut add = |address: &str, role: AddressRole, msg_type: Option<&str>| {
+            if self.watched.contains_key(address) {
+                found
+                    .entry((address.to_string(), role))
+                    .or_insert_with(|| msg_type.map(String::from));
+            }
+        };
+        for m in &tx.tx.body.messages {
+            let msg_type = m.get("@type").and_then(Value::as_str);
+            let fields = SIGNER_FIELDS
+                .iter()
+                .map(|f| (f, AddressRole::Signer))
+                .chain(RECIPIENT_FIELDS.iter().map(|f| (f, AddressRole::Recipient)))
+                .chain(CONTRACT_FIELDS.iter().map(|f| (f, AddressRole::Contract)));
+            for (field, role) in fields {
+                if let Some(address) = m.get(*field).and_then(Value::as_str) {
+                    add(address, role, msg_type);
+                }
+            }
+        }
+        for event in tx.log_events() {
+            let msg_type = tx.msg_type(event.msg_index);
+            for (key, value) in event.attributes.iter() {
+                let role = match key.as_str() {
+                    "sender" | "spender" => AddressRole::Sender,
+                    "recipient" | "receiver" => AddressRole::Recipient,
+                    "contract_address" | "_contract_address" => AddressRole::Contract,
+                    _ => continue,
+                };
+                add(value, role, msg_type);
+            }
+        }
+        found
+    }
+}
+impl Actor for WatchlistActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for WatchlistActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Watchlist Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageTX> for WatchlistActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        if self.watched.is_empty() {
+            return;
+        }
+        let failed = msg.tx.is_failed();
+        // the roles each watched address has in the tx, with the first message type involved
+        let mut involved: BTreeMap<String, (Vec<AddressRole>, Option<String>)> = BTreeMap::new();
+        for ((address, role), msg_type) in self.scan(&msg.tx) {
+            let watched = match self.watched.get(&address) {
+                Some(watched) if watched.reports(role) => watched,
+                _ => continue,
+            };
+            Broker::<SystemBroker>::issue_async(MessageAddressActivity {
+                height: msg.tx.height,
+                txhash: msg.tx.txhash.clone(),
+                address: address.clone(),
+                label: watched.label.clone(),
+                role,
+                msg_type: msg_type.clone(),
+                failed,
+            });
+            let (roles, first_msg_type) = involved.entry(address).or_insert((vec![], None));
+            roles.push(role);
+            if first_msg_type.is_none() {
+                *first_msg_type = msg_type;
+            }
+        }
+        // one event per address, so a tx raises a single alert for each watched address
+        for (address, (roles, msg_type)) in involved {
+            let watched = &self.watched[&address];
+            let roles = roles
+                .iter()
+                .map(|role| format!("{:?}", role))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let message = format!(
+                "{} ({}) {} in {}{}",
+                watched.label,
+                address,
+                roles,
+                msg_type.as_deref().unwrap_or("tx"),
+                if failed { " (failed)" } else { "" }
+            );
+            log::info!("Watchlist: {} {} {}", msg.tx.height, msg.tx.txhash, message);
+            Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+                height: msg.tx.height,
+                operator_address: watched.operator_address.clone().unwrap_or(address),
+                moniker: None,
+                event_type: watched.severity.clone(),
+                message,
+                hash: Some(msg.tx.txhash.clone()),
+            });
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::{Div, Mul};
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use rust_decimal::Decimal;
+use terra_rust_api::core_types::Coin;
+
+use crate::actor::BOND_DENOM;
+use crate::messages::{
+    MessageStakingDelegate, MessageStakingRedelegate, MessageStakingUndelegate, MessageValidator,
+    MessageValidatorEvent, MessageValidatorStakedTotal, ValidatorEventType,
+};
+use crate::BrokerType;
+use constellation_shared::MessageStop;
+
+/// a staking movement is flagged when it meets either limit
+#[derive(Clone, Debug, Default)]
+pub struct WhaleThreshold {
+    /// absolute amount, by denom. movements in other denoms are never flagged by amount
+    pub amount: HashMap<String, Decimal>,
+    /// fraction of the validator's bonded tokens, for movements of the bond denom
+    pub stake_fraction: Option<Decimal>,
+}
+
+/// announces large delegations, undelegations and redelegations
+pub struct WhaleActor {
+    pub threshold: WhaleThreshold,
+    /// only report movements involving these operators. None reports all
+    pub validators: Option<HashSet<String>>,
+    /// bonded tokens, by operator
+    pub tokens: HashMap<String, u64>,
+    /// moniker, by operator
+    pub monikers: HashMap<String, String>,
+}
+impl WhaleActor {
+    pub fn create(threshold: WhaleThreshold, validators: Option<HashSet<String>>) -> WhaleActor {
+        WhaleActor {
+            threshold,
+            validators,
+            tokens: Default::default(),
+            monikers: Default::default(),
+        }
+    }
+
+    fn watched(&self, operator_address: &str) -> bool {
+        match &self.validators {
+            Some(validators) => validators.contains(operator_address),
+            None => true,
+        }
+    }
+
+    fn is_whale(&self, operator_address: &str, amount: &Coin) -> bool {
+        if let Some(min_amount) = self.threshold.amount.get(&amount.denom) {
+            if amount.amount >= *min_amount {
+                return true;
+            }
+        }
+        if amount.denom != BOND_DENOM {
+            return false;
+        }
+        if let Some(stake_fraction) = self.threshold.stake_fraction {
+            if let Some(tokens) = self.tokens.get(operator_address) {
+                if *tokens > 0 && amount.amount >= Decimal::from(*tokens).mul(stake_fraction) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn name(&self, operator_address: &str) -> String {
+        self.monikers
+            .get(operator_address)
+            .cloned()
+            .unwrap_or_else(|| operator_address.into())
+    }
+
+    fn announce(&self, height: u64, operator_address: &str, message: String, txhash: &str) {
+        log::info!("Whale: {} {}", height, message);
+        Broker::<SystemBroker>::issue_async(MessageValidatorEvent {
+            height,
+            operator_address: operator_address.into(),
+            moniker: self.monikers.get(operator_address).cloned(),
+            event_type: ValidatorEventType::ANNOUNCE,
+            message,
+            hash: Some(txhash.into()),
+        });
+    }
+}
+
+/// uluna as LUNA, for messages
+fn luna(amount: &Coin) -> String {
+    if amount.denom == "uluna" {
+        format!("{:.2} LUNA", amount.amount.div(Decimal::from(1_000_000)))
+    } else {
+        format!("{}{}", amount.amount, amount.denom)
+    }
+}
+
+impl Actor for WhaleActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageStakingDelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStakingUndelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStakingRedelegate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidatorStakedTotal>(ctx);
+        self.subscribe_sync::<BrokerType, MessageValidator>(ctx);
+        self.subscribe_sync::<BrokerType, MessageStop>(ctx);
+    }
+}
+
+impl Handler<MessageStop> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: MessageStop, ctx: &mut Self::Context) {
+        log::info!("Whale Actor Stopping");
+        ctx.stop()
+    }
+}
+
+impl Handler<MessageValidatorStakedTotal> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidatorStakedTotal, _ctx: &mut Self::Context) {
+        self.tokens.insert(msg.operator_address, msg.tokens);
+    }
+}
+
+impl Handler<MessageValidator> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageValidator, _ctx: &mut Self::Context) {
+        self.monikers
+            .insert(msg.operator_address, msg.validator.description.moniker);
+    }
+}
+
+impl Handler<MessageStakingDelegate> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingDelegate, _ctx: &mut Self::Context) {
+        if self.watched(&msg.validator_address)
+            && self.is_whale(&msg.validator_address, &msg.amount)
+        {
+            let message = format!(
+                "{} delegated {} to {}",
+                msg.delegator_address,
+                luna(&msg.amount),
+                self.name(&msg.validator_address)
+            );
+            self.announce(msg.height, &msg.validator_address, message, &msg.txhash);
+        }
+    }
+}
+
+impl Handler<MessageStakingUndelegate> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingUndelegate, _ctx: &mut Self::Context) {
+        if self.watched(&msg.validator_address)
+            && self.is_whale(&msg.validator_address, &msg.amount)
+        {
+            let message = format!(
+                "{} undelegated {} from {}",
+                msg.delegator_address,
+                luna(&msg.amount),
+                self.name(&msg.validator_address)
+            );
+            self.announce(msg.height, &msg.validator_address, message, &msg.txhash);
+        }
+    }
+}
+
+impl Handler<MessageStakingRedelegate> for WhaleActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageStakingRedelegate, _ctx: &mut Self::Context) {
+        let message = format!(
+            "{} redelegated {} from {} to {}",
+            msg.delegator_address,
+            luna(&msg.amount),
+            self.name(&msg.validator_src_address),
+            self.name(&msg.validator_dst_address)
+        );
+        // announced once, against the first side it is large for
+        if let Some(operator_address) = [&msg.validator_src_address, &msg.validator_dst_address]
+            .iter()
+            .find(|operator_address| {
+                self.watched(operator_address) && self.is_whale(operator_address, &msg.amount)
+            })
+        {
+            self.announce(msg.height, operator_address, message, &msg.txhash);
+        }
+    }
+}
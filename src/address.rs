@@ -0,0 +1,131 @@
+/// bech32 addresses, as used for accounts, operators & consensus keys
+const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut chk: u32 = 1;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(*value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|b| b & 31));
+    expanded
+}
+
+/// decode a bech32 address into its prefix & data, verifying the checksum
+pub fn bech32_decode(address: &str) -> Option<(String, Vec<u8>)> {
+    if address.to_lowercase() != address && address.to_uppercase() != address {
+        return None;
+    }
+    let address = address.to_lowercase();
+    let (hrp, data) = address.rsplit_once('1')?;
+    if hrp.is_empty() || data.len() < 6 {
+        return None;
+    }
+    let values = data
+        .chars()
+        .map(|c| CHARSET.find(c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    let mut checked = hrp_expand(hrp);
+    checked.extend(values.iter());
+    if polymod(&checked) != 1 {
+        return None;
+    }
+    // regroup the 5 bit values, less the checksum, into bytes
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut bytes: Vec<u8> = vec![];
+    for value in &values[..values.len() - 6] {
+        acc = ((acc << 5) | u32::from(*value)) & 0xfff;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push(((acc >> bits) & 0xff) as u8);
+        }
+    }
+    if bits >= 5 || (acc << (8 - bits)) & 0xff != 0 {
+        return None;
+    }
+    Some((hrp.to_string(), bytes))
+}
+
+/// the data of a bech32 address as upper case hex, as used for consensus addresses in blocks
+pub fn bech32_to_hex(address: &str) -> Option<String> {
+    let (_, bytes) = bech32_decode(address)?;
+    Some(bytes.iter().map(|b| format!("{:02X}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valcons_to_hex() {
+        assert_eq!(
+            bech32_to_hex("terravalcons1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8y74n43").as_deref(),
+            Some("0A1B2C3D4E5F60718293A4B5C6D7E8F901234567")
+        );
+        assert_eq!(
+            bech32_to_hex("terravalcons160jmtu756mpmpgdzk0zdtehhpqvj5w6vz3zu9m").as_deref(),
+            Some("D3E5B5F3D4D6C3B0A1A2B3C4D5E6F708192A3B4C")
+        );
+    }
+
+    #[test]
+    fn same_key_any_prefix() {
+        let hex = bech32_to_hex("terravalcons1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8y74n43");
+        assert_eq!(
+            bech32_to_hex("terravaloper1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8sdx0es"),
+            hex
+        );
+        assert_eq!(
+            bech32_to_hex("terra1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8sz2jfr"),
+            hex
+        );
+        assert_eq!(
+            bech32_decode("terra1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8sz2jfr").map(|(hrp, _)| hrp),
+            Some("terra".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_checksum() {
+        assert_eq!(
+            bech32_to_hex("terravalcons1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8y74n44"),
+            None
+        );
+        // a changed character in the data
+        assert_eq!(
+            bech32_to_hex("terravalcons1pgdjc02wtas8rq5n5j6ud4lglyqjx3t9y74n43"),
+            None
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(bech32_to_hex(""), None);
+        assert_eq!(bech32_to_hex("terravalcons"), None);
+        assert_eq!(bech32_to_hex("terravalcons1b"), None);
+        assert_eq!(
+            bech32_to_hex("terraValcons1pgdjc02wtas8rq5n5j6ud4lglyqjx3t8y74n43"),
+            None
+        );
+    }
+}
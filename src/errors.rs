@@ -7,3 +7,13 @@ pub enum ObserverError {
     #[error("Socket Closed")]
     SocketClosed,
 }
+
+#[derive(Error, Debug)]
+pub enum LcdError {
+    #[error("LCD request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("LCD {url} answered {status}")]
+    Status { url: String, status: u16 },
+    #[error("LCD {url} returned an unexpected response: {reason}")]
+    Unexpected { url: String, reason: String },
+}
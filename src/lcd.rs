@@ -0,0 +1,128 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde_json::Value;
+use terra_rust_api::{staking_types, tendermint_types, Terra};
+
+use crate::errors::LcdError;
+
+/// the most the LCD (and tendermint) return in a page
+const PAGE_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+struct ValidatorsResult {
+    result: Vec<staking_types::Validator>,
+}
+
+/// the validators with a bond status (eg. BOND_STATUS_BONDED), over every page
+pub async fn validators(
+    terra: &Terra,
+    status: &str,
+) -> anyhow::Result<Vec<staking_types::Validator>> {
+    let mut validators: Vec<staking_types::Validator> = vec![];
+    for page in 1.. {
+        let args = format!("?status={}&page={}&limit={}", status, page, PAGE_LIMIT);
+        let result = terra
+            .send_cmd::<ValidatorsResult>("/staking/validators", Some(&args))
+            .await?
+            .result;
+        let last_page = result.len() < PAGE_LIMIT;
+        validators.extend(result);
+        if last_page {
+            break;
+        }
+    }
+    Ok(validators)
+}
+
+/// the latest tendermint validator set, over every page
+pub async fn validator_set(terra: &Terra) -> anyhow::Result<Vec<tendermint_types::Validator>> {
+    let mut validators: Vec<tendermint_types::Validator> = vec![];
+    let mut seen: HashSet<String> = HashSet::new();
+    for page in 1.. {
+        let result = match terra.tendermint().validatorsets(page, PAGE_LIMIT).await {
+            Ok(result) => result.result.validators,
+            // tendermint rejects a page past the end, which a full last page leads to
+            Err(e) if page > 1 && validators.len() % PAGE_LIMIT == 0 => {
+                log::debug!("Validator set ends at page {}: {}", page - 1, e);
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        let last_page = result.len() < PAGE_LIMIT;
+        for validator in result {
+            // an LCD ignoring the page would repeat the first one
+            if !seen.insert(validator.address.clone()) {
+                return Ok(validators);
+            }
+            validators.push(validator);
+        }
+        if last_page {
+            break;
+        }
+    }
+    Ok(validators)
+}
+
+/// the LCD endpoints used which terra-rust-api does not cover
+#[derive(Clone, Debug)]
+pub struct LcdClient {
+    pub url: String,
+    client: reqwest::Client,
+}
+impl LcdClient {
+    pub fn create(url: &str) -> LcdClient {
+        LcdClient {
+            url: url.trim_end_matches('/').into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<Value>, LcdError> {
+        let url = format!("{}{}", self.url, path);
+        let response = self.client.get(&url).send().await?;
+        let status = response.status();
+        if status.is_client_error() {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(LcdError::Status {
+                url,
+                status: status.as_u16(),
+            });
+        }
+        Ok(Some(response.json().await?))
+    }
+
+    /// the proposals in their voting period
+    pub async fn voting_proposals(&self) -> Result<Vec<Value>, LcdError> {
+        let path = "/cosmos/gov/v1beta1/proposals?proposal_status=2";
+        match self.get(path).await? {
+            Some(response) => match response.get("proposals").and_then(Value::as_array) {
+                Some(proposals) => Ok(proposals.clone()),
+                None => Err(LcdError::Unexpected {
+                    url: format!("{}{}", self.url, path),
+                    reason: "no proposals".into(),
+                }),
+            },
+            None => Err(LcdError::Unexpected {
+                url: format!("{}{}", self.url, path),
+                reason: "not found".into(),
+            }),
+        }
+    }
+
+    /// how voter voted on a proposal. the LCD answers with a client error when there is no vote
+    pub async fn vote(&self, proposal_id: &str, voter: &str) -> Result<Option<String>, LcdError> {
+        let path = format!(
+            "/cosmos/gov/v1beta1/proposals/{}/votes/{}",
+            proposal_id, voter
+        );
+        Ok(self.get(&path).await?.and_then(|vote| {
+            vote.pointer("/vote/option")
+                .or_else(|| vote.pointer("/vote/options/0/option"))
+                .and_then(Value::as_str)
+                .map(String::from)
+        }))
+    }
+}
@@ -1,11 +1,13 @@
 pub mod actor;
+mod address;
 mod b64;
 mod errors;
+mod lcd;
 pub mod messages;
 mod observer_intake;
 pub mod types;
 
 use actix_broker::SystemBroker;
 pub use messages::MessageTX;
-pub use observer_intake::run;
+pub use observer_intake::{replay, run};
 pub type BrokerType = SystemBroker;
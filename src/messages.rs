@@ -1,7 +1,11 @@
 use actix::prelude::*;
 
 use crate::types::TXandResult;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use terra_rust_api::core_types::Coin;
 use terra_rust_api::staking_types;
 use terra_rust_api::tendermint_types;
@@ -10,10 +14,69 @@ use terra_rust_api::tendermint_types;
 pub struct MessageTX {
     pub tx: TXandResult,
 }
+/// Sent for every block, with the validator which proposed it
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockProposed {
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    /// consensus address (hex) from the block header
+    pub proposer_address: String,
+    /// operator address, once it has been learnt from a proposer reward
+    pub operator_address: Option<String>,
+}
+/// Sent after every tx and event of a block has been sent
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockEnd {
+    pub height: u64,
+}
+#[derive(Clone, Debug)]
+pub struct BlockSigner {
+    /// position in the commit, which follows the order of the validator set
+    pub slot: usize,
+    /// consensus address (hex)
+    pub address: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// milliseconds after the median signature
+    pub delay_ms: Option<i64>,
+}
+/// Sent for every block, with who signed the previous block
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockSignatures {
+    /// the height that was signed
+    pub height: u64,
+    pub signed: Vec<BlockSigner>,
+    /// validators which voted nil
+    pub nil: Vec<BlockSigner>,
+    /// positions in the validator set which did not sign. absent signatures carry no address,
+    /// see MessageBlockAbsentSigners
+    pub absent_slots: Vec<usize>,
+    /// size of the validator set which signed
+    pub slots: usize,
+}
+#[derive(Clone, Debug)]
+pub struct AbsentSigner {
+    pub slot: usize,
+    /// consensus address (hex)
+    pub consensus_address: String,
+    pub operator_address: Option<String>,
+    pub moniker: Option<String>,
+}
+/// Sent for every block with absent signatures, once they are matched to the validator set
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockAbsentSigners {
+    /// the height that was signed
+    pub height: u64,
+    pub absent: Vec<AbsentSigner>,
+}
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct MessageBlockEventReward {
     pub height: u64,
+    pub time: DateTime<Utc>,
     pub is_begin: bool,
     pub is_proposer: bool,
     pub validator: String,
@@ -27,10 +90,36 @@ pub struct MessageBlockEventLiveness {
     pub tendermint_address: String,
     pub missed: usize,
 }
+/// Sent when a validator is slashed, for downtime or a double sign
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockEventSlash {
+    pub height: u64,
+    pub is_begin: bool,
+    /// bech32 consensus address
+    pub consensus_address: String,
+    pub operator_address: Option<String>,
+    /// eg. missing_signature or double_sign
+    pub reason: String,
+    pub power: Option<u64>,
+    pub burned: Vec<Coin>,
+    pub jailed: bool,
+}
+/// Sent when a validator is jailed
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageBlockEventJail {
+    pub height: u64,
+    pub is_begin: bool,
+    /// bech32 consensus address
+    pub consensus_address: String,
+    pub operator_address: Option<String>,
+}
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct MessageBlockEventCommission {
     pub height: u64,
+    pub time: DateTime<Utc>,
     pub is_begin: bool,
     pub validator: String,
     pub amount: Vec<Coin>,
@@ -39,10 +128,18 @@ pub struct MessageBlockEventCommission {
 #[rtype(result = "()")]
 pub struct MessageBlockEventExchangeRate {
     pub height: u64,
+    pub time: DateTime<Utc>,
     pub denom: String,
     pub exchange_rate: Decimal,
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DriftSeverity {
+    /// outside the warning band
+    Warning,
+    /// outside the critical band
+    Critical,
+}
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct MessagePriceDrift {
@@ -51,9 +148,39 @@ pub struct MessagePriceDrift {
     pub denom: String,
     pub average: Decimal,
     pub weighted_average: Decimal,
+    pub median: Decimal,
+    pub submitted: Decimal,
+    pub severity: DriftSeverity,
+    /// how many warning bands the submission is away from the median
+    pub band_multiple: Decimal,
+    pub txhash: String,
+}
+/// Sent periodically while a validator continues to drift on a denom
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessagePriceDriftOngoing {
+    pub height: u64,
+    pub operator_address: String,
+    pub denom: String,
+    pub start_height: u64,
+    pub periods: u64,
+    pub worst_severity: DriftSeverity,
+    pub max_band_multiple: Decimal,
+    pub median: Decimal,
     pub submitted: Decimal,
     pub txhash: String,
 }
+/// Sent when a drifting validator submits a price within the band again
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessagePriceDriftRecovered {
+    pub height: u64,
+    pub operator_address: String,
+    pub denom: String,
+    pub start_height: u64,
+    pub periods: u64,
+    pub worst_severity: DriftSeverity,
+}
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct MessagePriceAbstain {
@@ -62,6 +189,194 @@ pub struct MessagePriceAbstain {
     pub denoms: Vec<String>,
     pub txhash: String,
 }
+/// how a validator's vote fared in a single vote period
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OracleVoteOutcome {
+    /// every denom was within the reward band of the weighted median
+    Win,
+    /// no vote, a missing denom, or a denom outside the reward band
+    Miss,
+    /// one or more denoms were abstained
+    Abstain,
+}
+#[derive(Clone, Debug)]
+pub struct OraclePeriodVote {
+    pub operator_address: String,
+    pub weight: u64,
+    pub outcome: OracleVoteOutcome,
+}
+/// Sent at the end of each vote period with the outcome of every validator's vote
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageOracleVotePeriod {
+    pub height: u64,
+    pub votes: Vec<OraclePeriodVote>,
+}
+/// Sent at the end of a vote period for each bonded validator which did not vote
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageOracleMissedVote {
+    pub height: u64,
+    pub operator_address: String,
+    pub moniker: Option<String>,
+    pub last_seen: Option<u64>,
+    /// number of consecutive vote periods missed
+    pub consecutive: u64,
+}
+/// Sent when a validator's feeder keeps voting in the last block of the vote period
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageLateVotes {
+    pub height: u64,
+    pub operator_address: String,
+    /// votes in the last block of the period
+    pub late: usize,
+    /// recent votes considered
+    pub votes: usize,
+}
+/// Sets the balance of the oracle reward pool. sent by OracleRewardActor as it re-reads it
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageOracleRewardPool {
+    pub height: u64,
+    pub pool: Vec<Coin>,
+}
+/// Sent periodically with a validator's projected share of oracle rewards, and the rewards seen
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageOracleRewardEstimate {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub operator_address: String,
+    pub periods: u64,
+    pub wins: u64,
+    /// projected oracle rewards, by denom
+    pub projected: HashMap<String, Decimal>,
+    /// rewards distributed to the validator (all sources), by denom
+    pub actual: HashMap<String, Decimal>,
+}
+/// Sent when the chain's oracle rate for a denom moves further than the threshold from the
+/// reference price feed
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageReferencePriceDivergence {
+    pub height: u64,
+    pub denom: String,
+    pub exchange_rate: Decimal,
+    pub reference_rate: Decimal,
+    pub reference_time: DateTime<Utc>,
+    /// (exchange_rate - reference_rate) / reference_rate
+    pub divergence: Decimal,
+}
+/// Sent when a diverging denom is back within the threshold of the reference price feed
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageReferencePriceConverged {
+    pub height: u64,
+    pub denom: String,
+    pub exchange_rate: Decimal,
+    pub reference_rate: Decimal,
+    pub divergence: Decimal,
+}
+/// Sent when a periodic refresh finds an oracle parameter has changed
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageOracleParameterChanged {
+    pub height: u64,
+    pub parameter: String,
+    pub previous: String,
+    pub current: String,
+}
+/// Sent when a vote omits whitelisted denoms, or includes ones which aren't whitelisted
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageVoteWhitelistMismatch {
+    pub height: u64,
+    pub operator_address: String,
+    pub missing: Vec<String>,
+    pub unexpected: Vec<String>,
+    pub txhash: String,
+}
+/// Sent when an operator delegates their oracle votes to a feeder account
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageFeederDelegated {
+    pub height: u64,
+    pub operator_address: String,
+    pub feeder: String,
+    pub txhash: String,
+}
+/// Sent when a vote is submitted by a feeder other than the delegated one
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageFeederMismatch {
+    pub height: u64,
+    pub operator_address: String,
+    pub delegated: String,
+    pub feeder: String,
+    pub txhash: String,
+}
+/// Sent when a transaction carrying a validator's oracle vote fails
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageFeederVoteFailed {
+    pub height: u64,
+    pub operator_address: String,
+    pub feeder: String,
+    pub txhash: String,
+    pub raw_log: String,
+}
+/// gas & fees spent on a validator's oracle votes
+#[derive(Clone, Debug)]
+pub struct FeederCost {
+    pub operator_address: String,
+    pub feeder: String,
+    pub txs: u64,
+    pub failed_txs: u64,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+    /// fees paid, by denom
+    pub fees: HashMap<String, Decimal>,
+}
+/// Sent at the end of each cost window with the feeder costs of every validator seen voting
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageFeederCostSummary {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub costs: Vec<FeederCost>,
+}
+/// Sent for each delegation (or validator creation), including those made through authz
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageStakingDelegate {
+    pub height: u64,
+    pub txhash: String,
+    pub delegator_address: String,
+    pub validator_address: String,
+    pub amount: Coin,
+}
+/// Sent for each undelegation, including those made through authz
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageStakingUndelegate {
+    pub height: u64,
+    pub txhash: String,
+    pub delegator_address: String,
+    pub validator_address: String,
+    pub amount: Coin,
+}
+/// Sent for each redelegation, including those made through authz
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageStakingRedelegate {
+    pub height: u64,
+    pub txhash: String,
+    pub delegator_address: String,
+    pub validator_src_address: String,
+    pub validator_dst_address: String,
+    pub amount: Coin,
+}
 #[derive(Clone, Debug, Message)]
 #[rtype(result = "()")]
 pub struct MessageValidatorStakedTotal {
@@ -86,6 +401,13 @@ pub struct MessageValidator {
     pub validator: staking_types::Validator,
     pub tendermint: Option<tendermint_types::Validator>,
 }
+/// Sent when a validator is no longer returned by the LCD, in any status
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageValidatorRemoved {
+    pub height: u64,
+    pub operator_address: String,
+}
 
 /// Sent when system wants to notify places that an event on a validator occurred
 #[derive(Clone, Debug)]
@@ -117,3 +439,362 @@ pub struct MessageSendMessageEvent {
     pub message: String,
     pub hash: Option<String>,
 }
+
+/// Ask the oracle actor how a validator has performed over the last `periods` vote periods
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<OraclePerformance>")]
+pub struct GetOraclePerformance {
+    pub operator: String,
+    pub periods: usize,
+}
+#[derive(Clone, Debug)]
+pub struct OraclePerformance {
+    pub operator_address: String,
+    pub periods: usize,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub wins: usize,
+    pub misses: usize,
+    pub abstains: usize,
+    pub win_rate: Decimal,
+    /// mean of |submitted - median| / median, per denom
+    pub mean_abs_deviation: HashMap<String, Decimal>,
+}
+
+/// Ask the oracle actor which feeder account votes for a validator
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<ValidatorFeeder>")]
+pub struct GetValidatorFeeder {
+    pub operator: String,
+}
+#[derive(Clone, Debug)]
+pub struct ValidatorFeeder {
+    pub operator_address: String,
+    /// set via MsgDelegateFeedConsent
+    pub delegated: Option<String>,
+    /// last account seen submitting a vote
+    pub observed: Option<String>,
+}
+/// Ask the oracle actor which validator a feeder account votes for
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<String>")]
+pub struct GetFeederValidator {
+    pub feeder: String,
+}
+
+/// Ask the oracle actor how early or late in the vote period a validator's votes land
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<VoteTiming>")]
+pub struct GetVoteTiming {
+    pub operator: String,
+}
+#[derive(Clone, Debug)]
+pub struct VoteTiming {
+    pub operator_address: String,
+    pub votes: usize,
+    /// mean block offset within the vote period
+    pub mean_offset: Decimal,
+    /// number of votes at each block offset within the vote period
+    pub histogram: Vec<u64>,
+    pub last_block_votes: u64,
+}
+
+/// an on-chain oracle exchange rate
+#[derive(Clone, Debug)]
+pub struct ExchangeRatePoint {
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    pub exchange_rate: Decimal,
+}
+/// open/high/low/close of a denom's exchange rate over an interval
+#[derive(Clone, Debug)]
+pub struct ExchangeRateCandle {
+    pub denom: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub first_height: u64,
+    pub last_height: u64,
+}
+/// Sent when an exchange rate candle closes
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageExchangeRateCandle {
+    pub candle: ExchangeRateCandle,
+}
+/// Ask for the exchange rate in effect at a height
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<ExchangeRatePoint>")]
+pub struct GetExchangeRateAt {
+    pub denom: String,
+    pub height: u64,
+}
+/// Ask for the exchange rates set between two heights (inclusive)
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Vec<ExchangeRatePoint>")]
+pub struct GetExchangeRateRange {
+    pub denom: String,
+    pub from_height: u64,
+    pub to_height: u64,
+}
+/// Ask for the exchange rate candles which start between two times (inclusive)
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Vec<ExchangeRateCandle>")]
+pub struct GetExchangeRateCandles {
+    pub denom: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// rewards & commission earned by a validator, by denom, with their value in the quote denom
+#[derive(Clone, Debug, Default)]
+pub struct ValidatorRewardSummary {
+    pub operator_address: String,
+    pub rewards: HashMap<String, Decimal>,
+    pub proposer_rewards: HashMap<String, Decimal>,
+    pub commission: HashMap<String, Decimal>,
+    pub rewards_value: Decimal,
+    pub proposer_rewards_value: Decimal,
+    pub commission_value: Decimal,
+}
+/// Sent when a reward window closes, with what every validator earned in it
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageRewardSummary {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub quote_denom: String,
+    pub validators: Vec<ValidatorRewardSummary>,
+}
+/// Ask what a validator has earned in the current (or previous) reward window
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<ValidatorRewardSummary>")]
+pub struct GetRewardSummary {
+    pub operator: String,
+    pub previous: bool,
+}
+
+/// how often a validator proposed blocks, compared to its share of voting power
+#[derive(Clone, Debug)]
+pub struct ProposerStat {
+    /// consensus address (hex)
+    pub consensus_address: String,
+    /// for display, once it is known
+    pub operator_address: Option<String>,
+    /// tendermint voting power
+    pub power: u64,
+    pub proposed: u64,
+    pub expected: Decimal,
+}
+/// Sent every report window with the proposal frequency of each validator
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageProposerStats {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub blocks: u64,
+    pub validators: Vec<ProposerStat>,
+}
+/// Ask how often a validator has proposed in the current report window
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Option<ProposerStat>")]
+pub struct GetProposerStat {
+    pub operator: String,
+}
+
+/// how a watched address took part in a tx
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AddressRole {
+    /// signed one of the tx's messages
+    Signer,
+    /// sent or spent coins
+    Sender,
+    /// received coins
+    Recipient,
+    /// was the contract executed or emitting events
+    Contract,
+}
+/// Sent when a watched address is involved in a tx
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageAddressActivity {
+    pub height: u64,
+    pub txhash: String,
+    pub address: String,
+    pub label: String,
+    pub role: AddressRole,
+    /// the message type involving the address, when known
+    pub msg_type: Option<String>,
+    pub failed: bool,
+}
+
+/// why a tx failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TxErrorCategory {
+    OutOfGas,
+    InsufficientFee,
+    SequenceMismatch,
+    /// rejected by the oracle module (late/duplicate vote, bad denom, feeder not delegated ...)
+    OracleVote,
+    /// a wasm contract returned an error
+    Contract,
+    Other,
+}
+/// Sent for each failed tx
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageTxFailed {
+    pub height: u64,
+    pub txhash: String,
+    pub signers: Vec<String>,
+    pub msg_types: Vec<String>,
+    pub category: TxErrorCategory,
+    pub code: Option<u32>,
+    pub codespace: Option<String>,
+    pub raw_log: String,
+    pub gas_wanted: u64,
+    pub gas_used: u64,
+}
+
+/// Sent for each successful MsgSubmitProposal
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageGovProposalSubmitted {
+    pub height: u64,
+    pub txhash: String,
+    pub proposal_id: u64,
+    pub proposer: String,
+    pub title: Option<String>,
+    /// the type of the proposal's content
+    pub content_type: Option<String>,
+    pub initial_deposit: Vec<Coin>,
+}
+/// Sent for each successful MsgDeposit
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageGovDeposit {
+    pub height: u64,
+    pub txhash: String,
+    pub proposal_id: u64,
+    pub depositor: String,
+    pub amount: Vec<Coin>,
+}
+/// Sent when a proposal reaches its minimum deposit and enters the voting period
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageGovVotingStarted {
+    pub height: u64,
+    pub txhash: String,
+    pub proposal_id: u64,
+}
+/// Sent for each successful MsgVote / MsgVoteWeighted
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageGovVote {
+    pub height: u64,
+    pub txhash: String,
+    pub proposal_id: u64,
+    pub voter: String,
+    /// VOTE_OPTION_*. weighted votes are listed as option:weight, comma separated
+    pub option: String,
+}
+/// Sent when a proposal's voting (or deposit) period ends
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageGovProposalEnded {
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    pub proposal_id: u64,
+    /// proposal_passed, proposal_rejected, proposal_failed, proposal_dropped ...
+    pub result: String,
+}
+
+/// Sent for each successful MsgSwap / MsgSwapSend
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageSwap {
+    pub height: u64,
+    pub time: DateTime<Utc>,
+    pub txhash: String,
+    pub trader: String,
+    /// the trader, unless it was a MsgSwapSend
+    pub recipient: String,
+    pub offer: Coin,
+    pub ask_denom: String,
+    /// what was received, after the fee. from the swap event
+    pub swap_coin: Option<Coin>,
+    pub swap_fee: Option<Coin>,
+}
+/// swaps on one denom pair over a period
+#[derive(Clone, Debug)]
+pub struct SwapPairVolume {
+    pub offer_denom: String,
+    pub ask_denom: String,
+    pub swaps: u64,
+    pub offer_amount: Decimal,
+    pub ask_amount: Decimal,
+    /// in the ask denom
+    pub fees: Decimal,
+    /// received vs. the oracle rate, fee included. averaged over the offered amount
+    pub spread: Option<Decimal>,
+}
+/// Sent at the end of each swap period
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageSwapSummary {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub pairs: Vec<SwapPairVolume>,
+}
+
+/// Sent for each successful MsgExecuteContract
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageWasmExecute {
+    pub height: u64,
+    pub txhash: String,
+    pub sender: String,
+    pub contract: String,
+    pub execute_msg: Value,
+    pub coins: Vec<Coin>,
+}
+/// Sent for each successful MsgInstantiateContract
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageWasmInstantiate {
+    pub height: u64,
+    pub txhash: String,
+    pub sender: String,
+    pub admin: Option<String>,
+    pub code_id: u64,
+    /// from the instantiate_contract event
+    pub contract_address: Option<String>,
+    pub init_msg: Value,
+    pub init_coins: Vec<Coin>,
+}
+/// Sent for the attributes a contract emits in a wasm/from_contract event
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "()")]
+pub struct MessageWasmEvent {
+    pub height: u64,
+    /// None for begin/end block events
+    pub txhash: Option<String>,
+    /// wasm or from_contract
+    pub event_type: String,
+    pub contract_address: String,
+    pub action: Option<String>,
+    pub attributes: Vec<(String, String)>,
+}
+/// Ask for the execute messages & events of one contract to be sent to a recipient
+#[derive(Clone, Message)]
+#[rtype(result = "()")]
+pub struct SubscribeWasmContract {
+    pub contract_address: String,
+    pub events: Recipient<MessageWasmEvent>,
+    pub executes: Option<Recipient<MessageWasmExecute>>,
+}
@@ -1,16 +1,23 @@
 use futures::{SinkExt, StreamExt};
 
+use crate::address::bech32_to_hex;
 use crate::errors::ObserverError::{SocketBinary, SocketClosed};
 use crate::messages::{
-    MessageBlockEventCommission, MessageBlockEventExchangeRate, MessageBlockEventLiveness,
-    MessageBlockEventReward, MessageTX,
+    BlockSigner, MessageBlockEnd, MessageBlockEventCommission, MessageBlockEventExchangeRate,
+    MessageBlockEventJail, MessageBlockEventLiveness, MessageBlockEventReward,
+    MessageBlockEventSlash, MessageBlockProposed, MessageBlockSignatures, MessageGovProposalEnded,
+    MessageTX, MessageWasmEvent,
 };
-use crate::types::{NewBlock, NewBlockEvent};
+use crate::types::{contract_attributes, NewBlock, NewBlockEvent};
 use actix_broker::{Broker, SystemBroker};
+use chrono::{DateTime, Utc};
 use constellation_shared::AppState;
 use rust_decimal::prelude::FromStr;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
 use terra_rust_api::core_types::Coin;
 use tokio::time::Duration;
 use tokio_tungstenite::connect_async;
@@ -22,8 +29,26 @@ pub const VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 /// NAME of package
 pub const NAME: Option<&'static str> = option_env!("CARGO_PKG_NAME");
 
-// TODO add proposing validator to messages.
+/// what the intake remembers between blocks
+#[derive(Default)]
+struct IntakeState {
+    /// (height, consensus address) of the last block's proposer
+    last_proposer: Option<(u64, String)>,
+    /// operator address by consensus address, learnt from proposer rewards
+    consensus_operator: HashMap<String, String>,
+}
+
+impl IntakeState {
+    /// operator address of a bech32 (terravalcons) consensus address, if it has been learnt
+    fn valcons_operator(&self, address: &str) -> Option<String> {
+        self.consensus_operator
+            .get(&bech32_to_hex(address)?)
+            .cloned()
+    }
+}
+
 pub async fn run(_state: AppState, connect_addr: String) {
+    let mut intake = IntakeState::default();
     loop {
         match Request::builder()
             .header(
@@ -49,7 +74,7 @@ pub async fn run(_state: AppState, connect_addr: String) {
                             Ok(_) => {
                                 while let Some(message) = ws_stream.next().await {
                                     match message {
-                                        Ok(msg) => match handle_message(msg) {
+                                        Ok(msg) => match handle_message(msg, &mut intake) {
                                             Ok(exit) => {
                                                 if let Some(response) = exit {
                                                     if let Err(e) = ws_stream.send(response).await {
@@ -84,7 +109,25 @@ pub async fn run(_state: AppState, connect_addr: String) {
     }
 }
 
-fn handle_message(msg: Message) -> anyhow::Result<Option<Message>> {
+/// replay blocks recorded from the observer (one new_block message per line) without a connection.
+/// returns the number of blocks pushed to the actors
+pub fn replay<P: AsRef<Path>>(path: P) -> anyhow::Result<usize> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut intake = IntakeState::default();
+    let mut count = 0;
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let new_block = serde_json::from_str::<NewBlock>(&line)?;
+        process_block_emit(&new_block, &mut intake)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn handle_message(msg: Message, intake: &mut IntakeState) -> anyhow::Result<Option<Message>> {
     match msg {
         Message::Text(text) => match serde_json::from_str::<NewBlock>(&text) {
             Ok(new_block) => {
@@ -93,7 +136,7 @@ fn handle_message(msg: Message) -> anyhow::Result<Option<Message>> {
                     new_block.chain_id,
                     new_block.data.block.header.height
                 );
-                if let Err(e) = process_block_emit(&new_block) {
+                if let Err(e) = process_block_emit(&new_block, intake) {
                     log::error!("Error pushing block to actors: {}", e);
                     Err(e)
                 } else {
@@ -118,8 +161,35 @@ fn handle_message(msg: Message) -> anyhow::Result<Option<Message>> {
         }
     }
 }
-fn process_block_emit(block: &NewBlock) -> anyhow::Result<()> {
+fn process_block_emit(block: &NewBlock, intake: &mut IntakeState) -> anyhow::Result<()> {
     let height = block.data.block.header.height;
+    let time = block.data.block.header.time;
+    let proposer_address = block.data.block.header.proposer_address.clone();
+    // distribution pays the proposer of the previous block, which tells us its operator
+    if let Some((last_height, last_proposer)) = intake.last_proposer.take() {
+        if last_height + 1 == height {
+            if let Some(operator_address) = block
+                .data
+                .result_begin_block
+                .events
+                .iter()
+                .filter(|event| event.s_type == "proposer_reward")
+                .find_map(|event| get_required_kv(&event.attribute_map(), "validator"))
+            {
+                intake
+                    .consensus_operator
+                    .insert(last_proposer, operator_address);
+            }
+        }
+    }
+    Broker::<SystemBroker>::issue_async(MessageBlockProposed {
+        height,
+        time,
+        proposer_address: proposer_address.clone(),
+        operator_address: intake.consensus_operator.get(&proposer_address).cloned(),
+    });
+    intake.last_proposer = Some((height, proposer_address));
+    process_signatures(block);
     if let Some(txs) = &block.data.txs {
         txs.iter().for_each(|tx| {
             Broker::<SystemBroker>::issue_async(MessageTX { tx: tx.clone() });
@@ -130,13 +200,13 @@ fn process_block_emit(block: &NewBlock) -> anyhow::Result<()> {
         .result_begin_block
         .events
         .iter()
-        .for_each(|event| process_event(height, true, event));
+        .for_each(|event| process_event(height, time, true, event, intake));
     match &block.data.result_end_block.events {
         None => {}
         Some(end_block_events) => {
             end_block_events
                 .iter()
-                .for_each(|event| process_event(height, false, event));
+                .for_each(|event| process_event(height, time, false, event, intake));
         }
     }
     let v = &block.data.result_end_block.validator_updates;
@@ -148,9 +218,69 @@ fn process_block_emit(block: &NewBlock) -> anyhow::Result<()> {
             f.power
         )
     });
+    Broker::<SystemBroker>::issue_async(MessageBlockEnd { height });
 
     Ok(())
 }
+/// who signed the previous block, from this block's last_commit.
+/// absent signatures carry no address, so only their position in the validator set is known
+fn process_signatures(block: &NewBlock) {
+    let commit = match &block.data.block.last_commit {
+        Some(commit) => commit,
+        None => return,
+    };
+    let height = commit.height;
+    let mut signatures: Vec<(usize, String, Option<DateTime<Utc>>)> = vec![];
+    let mut nil: Vec<BlockSigner> = vec![];
+    let mut absent_slots: Vec<usize> = vec![];
+    for (slot, signature) in commit.signatures.iter().enumerate() {
+        match signature.block_id_flag {
+            // BLOCK_ID_FLAG_COMMIT
+            2 => signatures.push((
+                slot,
+                signature.validator_address.clone(),
+                DateTime::parse_from_rfc3339(&signature.timestamp)
+                    .map(|timestamp| timestamp.with_timezone(&Utc))
+                    .ok(),
+            )),
+            // BLOCK_ID_FLAG_NIL
+            3 => nil.push(BlockSigner {
+                slot,
+                address: signature.validator_address.clone(),
+                timestamp: None,
+                delay_ms: None,
+            }),
+            _ => absent_slots.push(slot),
+        }
+    }
+
+    let mut timestamps = signatures
+        .iter()
+        .filter_map(|signature| signature.2)
+        .collect::<Vec<_>>();
+    timestamps.sort();
+    let median = timestamps.get(timestamps.len() / 2).cloned();
+    let signed = signatures
+        .into_iter()
+        .map(|(slot, address, timestamp)| BlockSigner {
+            delay_ms: match (timestamp, median) {
+                (Some(timestamp), Some(median)) => Some((timestamp - median).num_milliseconds()),
+                _ => None,
+            },
+            slot,
+            address,
+            timestamp,
+        })
+        .collect::<Vec<_>>();
+
+    Broker::<SystemBroker>::issue_async(MessageBlockSignatures {
+        height,
+        signed,
+        nil,
+        absent_slots,
+        slots: commit.signatures.len(),
+    });
+}
 fn get_required_kv(hash_map: &HashMap<String, Option<String>>, key: &str) -> Option<String> {
     if let Some(val) = hash_map.get(key) {
         val.as_ref().map(|value| value.into())
@@ -158,7 +288,13 @@ fn get_required_kv(hash_map: &HashMap<String, Option<String>>, key: &str) -> Opt
         None
     }
 }
-fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
+fn process_event(
+    height: u64,
+    time: DateTime<Utc>,
+    is_begin: bool,
+    event: &NewBlockEvent,
+    intake: &IntakeState,
+) {
     let attributes = event.attribute_map();
 
     match event.s_type.as_str() {
@@ -168,6 +304,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     Ok(coins) => {
                         Broker::<SystemBroker>::issue_async(MessageBlockEventReward {
                             height,
+                            time,
                             is_begin,
                             is_proposer: false,
                             validator: validator_value,
@@ -188,6 +325,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     log::debug!("Rewards Zero? {} {}", height, validator_value);
                     Broker::<SystemBroker>::issue_async(MessageBlockEventReward {
                         height,
+                        time,
                         is_begin,
                         is_proposer: false,
                         validator: validator_value,
@@ -207,6 +345,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     Ok(coins) => {
                         Broker::<SystemBroker>::issue_async(MessageBlockEventReward {
                             height,
+                            time,
                             is_begin,
                             is_proposer: true,
                             validator: validator_value,
@@ -227,6 +366,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     log::debug!("Proposer Rewards Zero? {} {}", height, validator_value);
                     Broker::<SystemBroker>::issue_async(MessageBlockEventReward {
                         height,
+                        time,
                         is_begin,
                         is_proposer: true,
                         validator: validator_value,
@@ -246,6 +386,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     Ok(coins) => {
                         Broker::<SystemBroker>::issue_async(MessageBlockEventCommission {
                             height,
+                            time,
                             is_begin,
                             validator: validator_value,
                             amount: coins,
@@ -258,6 +399,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                 None => {
                     Broker::<SystemBroker>::issue_async(MessageBlockEventCommission {
                         height,
+                        time,
                         is_begin,
                         validator: validator_value,
                         amount: vec![],
@@ -270,6 +412,63 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                 attributes
             ),
         },
+        "slash" => match get_required_kv(&attributes, "address") {
+            Some(consensus_address) => {
+                let reason = get_required_kv(&attributes, "reason").unwrap_or_default();
+                let power = get_required_kv(&attributes, "power").and_then(|p| p.parse().ok());
+                let burned = match get_required_kv(&attributes, "burned_coins") {
+                    Some(burned_str) => Coin::parse_coins(&burned_str).unwrap_or_else(|e| {
+                        log::error!("Bad Coin String: {} {} {}", height, burned_str, e);
+                        vec![]
+                    }),
+                    None => vec![],
+                };
+                let jailed = get_required_kv(&attributes, "jailed").is_some();
+                let operator_address = intake.valcons_operator(&consensus_address);
+                log::warn!(
+                    "slash: {} {} {} reason:{} power:{:?} jailed:{}",
+                    height,
+                    consensus_address,
+                    operator_address.clone().unwrap_or_default(),
+                    reason,
+                    power,
+                    jailed
+                );
+                Broker::<SystemBroker>::issue_async(MessageBlockEventSlash {
+                    height,
+                    is_begin,
+                    consensus_address,
+                    operator_address,
+                    reason,
+                    power,
+                    burned,
+                    jailed,
+                });
+            }
+            // keeper.Jail emits a slash event with only the jailed (consensus) address
+            None => match get_required_kv(&attributes, "jailed") {
+                Some(consensus_address) => {
+                    let operator_address = intake.valcons_operator(&consensus_address);
+                    log::warn!(
+                        "jail: {} {} {}",
+                        height,
+                        consensus_address,
+                        operator_address.clone().unwrap_or_default()
+                    );
+                    Broker::<SystemBroker>::issue_async(MessageBlockEventJail {
+                        height,
+                        is_begin,
+                        consensus_address,
+                        operator_address,
+                    });
+                }
+                None => log::warn!(
+                    "Expecting address or jailed key for slash event {} {:#?}",
+                    height,
+                    attributes
+                ),
+            },
+        },
         "liveness" => {
             let missed_blocks_o = get_required_kv(&attributes, "missed_blocks");
             let address_o = get_required_kv(&attributes, "address");
@@ -335,6 +534,7 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                     if let Ok(ex_rate) = Decimal::from_str(&exchange_rate) {
                         Broker::<SystemBroker>::issue_async(MessageBlockEventExchangeRate {
                             height,
+                            time,
                             denom,
                             exchange_rate: ex_rate,
                         });
@@ -394,6 +594,48 @@ fn process_event(height: u64, is_begin: bool, event: &NewBlockEvent) {
                 annual_provisions_o.unwrap_or_default()
             )
         }
+        "active_proposal" | "inactive_proposal" => {
+            let proposal_id_o =
+                get_required_kv(&attributes, "proposal_id").and_then(|id| id.parse().ok());
+            let result = get_required_kv(&attributes, "proposal_result").unwrap_or_default();
+            match proposal_id_o {
+                Some(proposal_id) => {
+                    log::info!("{}: {} {} {}", event.s_type, height, proposal_id, result);
+                    Broker::<SystemBroker>::issue_async(MessageGovProposalEnded {
+                        height,
+                        time,
+                        proposal_id,
+                        result,
+                    });
+                }
+                None => log::warn!(
+                    "Expecting proposal_id for {} event {} {:#?}",
+                    event.s_type,
+                    height,
+                    attributes
+                ),
+            }
+        }
+        "wasm" | "from_contract" => {
+            let attributes = event
+                .attributes
+                .iter()
+                .map(|attr| (attr.key.clone(), attr.value.clone().unwrap_or_default()))
+                .collect::<Vec<_>>();
+            for (contract_address, attributes) in contract_attributes(&attributes) {
+                Broker::<SystemBroker>::issue_async(MessageWasmEvent {
+                    height,
+                    txhash: None,
+                    event_type: event.s_type.clone(),
+                    contract_address,
+                    action: attributes
+                        .iter()
+                        .find(|(key, _)| key == "action")
+                        .map(|(_, value)| value.clone()),
+                    attributes,
+                });
+            }
+        }
         "coin_spent" => {
             // amount
             // spender
@@ -30,6 +30,19 @@ pub struct NewBlockData {
     pub txs: Option<Vec<TXandResult>>,
     pub supply: Vec<Coin>,
 }
+/// message fields holding the signer
+pub const SIGNER_FIELDS: &[&str] = &[
+    "sender",
+    "from_address",
+    "delegator_address",
+    "feeder",
+    "voter",
+    "depositor",
+    "proposer",
+    "trader",
+    "creator",
+];
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct TXandResult {
     #[serde(with = "terra_u64_format")]
@@ -44,16 +57,123 @@ pub struct TXandResult {
     pub tx: TxOuter,
     #[serde(with = "terra_datetime_format")]
     pub timestamp: DateTime<Utc>,
+    /// non-zero when the tx failed
+    #[serde(default)]
+    pub code: Option<u32>,
+    #[serde(default)]
+    pub codespace: Option<String>,
     // body
     // signatures
     // auth_info
 }
+impl TXandResult {
+    /// a failed tx has a non-zero code, or no logs and its raw_log holds the error
+    /// instead of the json logs
+    pub fn is_failed(&self) -> bool {
+        if let Some(code) = self.code {
+            return code != 0;
+        }
+        match &self.logs {
+            Some(logs) if !logs.is_empty() => false,
+            _ => !self.raw_log.is_empty() && !self.raw_log.starts_with('['),
+        }
+    }
+    /// fees paid for the tx
+    pub fn fee(&self) -> Vec<Coin> {
+        self.tx
+            .auth_info
+            .as_ref()
+            .map(|auth_info| auth_info.fee.amount.clone())
+            .unwrap_or_default()
+    }
+    /// the accounts signing the tx's messages, in order of first appearance
+    pub fn signers(&self) -> Vec<String> {
+        let mut signers: Vec<String> = vec![];
+        for m in &self.tx.body.messages {
+            if let Some(signer) = SIGNER_FIELDS
+                .iter()
+                .find_map(|field| m.get(*field).and_then(Value::as_str))
+            {
+                if !signers.iter().any(|s| s == signer) {
+                    signers.push(signer.to_string());
+                }
+            }
+        }
+        signers
+    }
+    /// the type of the message at msg_index
+    pub fn msg_type(&self, msg_index: usize) -> Option<&str> {
+        self.tx
+            .body
+            .messages
+            .get(msg_index)
+            .and_then(|m| m.get("@type"))
+            .and_then(Value::as_str)
+    }
+    /// events from the tx logs, flattened over the messages
+    pub fn log_events(&self) -> Vec<TxLogEvent> {
+        self.logs
+            .iter()
+            .flatten()
+            .flat_map(|log| {
+                log.events.iter().map(move |event| TxLogEvent {
+                    msg_index: log.msg_index,
+                    s_type: event.s_type.clone(),
+                    attributes: event
+                        .attributes
+                        .iter()
+                        .map(|attr| (attr.key.clone(), attr.value.clone().unwrap_or_default()))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// an event from a tx log
+#[derive(Clone, Debug)]
+pub struct TxLogEvent {
+    pub msg_index: usize,
+    pub s_type: String,
+    /// (key, value) in order. keys can repeat when several actions are in one event
+    pub attributes: Vec<(String, String)>,
+}
+impl TxLogEvent {
+    /// the first value for key
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+    /// split the attributes into one map per action, as events of the same type are merged
+    /// in the log. each action's attributes start with first_key
+    pub fn actions(&self, first_key: &str) -> Vec<HashMap<String, String>> {
+        let mut actions: Vec<HashMap<String, String>> = vec![];
+        for (key, value) in &self.attributes {
+            if key == first_key {
+                actions.push(HashMap::new());
+            }
+            if let Some(action) = actions.last_mut() {
+                action.insert(key.clone(), value.clone());
+            }
+        }
+        actions
+    }
+}
 
 #[derive(Deserialize, Clone, Serialize, Debug)]
 pub struct TxOuter {
     #[serde(rename = "@type")]
     pub s_type: String,
     pub body: BlockTransaction,
+    #[serde(default)]
+    pub auth_info: Option<AuthInfo>,
+}
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct AuthInfo {
+    pub fee: Fee,
+    // signer_infos
 }
 #[derive(Deserialize, Clone, Serialize, Debug)]
 pub struct BlockTransaction {
@@ -62,19 +182,111 @@ pub struct BlockTransaction {
     //pub signatures: Vec<String>,
     pub memo: String,
 }
+/// /terra.wasm.v1beta1.MsgExecuteContract
 #[derive(Deserialize, Clone, Serialize, Debug)]
 pub struct BlockMsg {
     #[serde(rename = "@type")]
     pub s_type: String,
     pub execute_msg: Option<serde_json::Value>,
-    // sender
-    // contract
-    // coins
+    #[serde(default)]
+    pub sender: String,
+    #[serde(default)]
+    pub contract: String,
+    #[serde(default)]
+    pub coins: Vec<Coin>,
 }
+/// /terra.wasm.v1beta1.MsgInstantiateContract
 #[derive(Deserialize, Clone, Serialize, Debug)]
-pub struct Fee {
+pub struct MsgInstantiateContract {
+    pub sender: String,
+    #[serde(default)]
+    pub admin: Option<String>,
+    #[serde(with = "terra_u64_format")]
+    pub code_id: u64,
+    pub init_msg: Value,
+    #[serde(default)]
+    pub init_coins: Vec<Coin>,
+}
+/// contract messages are json, but may arrive base64 encoded
+pub fn decode_contract_msg(msg: Value) -> Value {
+    match &msg {
+        Value::String(encoded) => base64::decode(encoded)
+            .ok()
+            .and_then(|decoded| serde_json::from_slice(&decoded).ok())
+            .unwrap_or(msg),
+        _ => msg,
+    }
+}
+/// split the attributes of a wasm/from_contract event by the contract emitting them.
+/// each contract's attributes start with its contract_address
+pub fn contract_attributes(
+    attributes: &[(String, String)],
+) -> Vec<(String, Vec<(String, String)>)> {
+    let mut contracts: Vec<(String, Vec<(String, String)>)> = vec![];
+    for (key, value) in attributes {
+        if key == "contract_address" || key == "_contract_address" {
+            contracts.push((value.clone(), vec![]));
+        } else if let Some((_, contract)) = contracts.last_mut() {
+            contract.push((key.clone(), value.clone()));
+        }
+    }
+    contracts
+}
+/// /cosmos.gov.v1beta1.MsgSubmitProposal
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgSubmitProposal {
+    pub content: Value,
+    pub initial_deposit: Vec<Coin>,
+    pub proposer: String,
+}
+/// /cosmos.gov.v1beta1.MsgDeposit
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgDeposit {
+    #[serde(with = "terra_u64_format")]
+    pub proposal_id: u64,
+    pub depositor: String,
     pub amount: Vec<Coin>,
+}
+/// /cosmos.gov.v1beta1.MsgVote
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgVote {
     #[serde(with = "terra_u64_format")]
+    pub proposal_id: u64,
+    pub voter: String,
+    pub option: String,
+}
+/// /cosmos.gov.v1beta1.MsgVoteWeighted
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgVoteWeighted {
+    #[serde(with = "terra_u64_format")]
+    pub proposal_id: u64,
+    pub voter: String,
+    pub options: Vec<WeightedVoteOption>,
+}
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct WeightedVoteOption {
+    pub option: String,
+    pub weight: String,
+}
+/// /terra.market.v1beta1.MsgSwap
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgSwap {
+    pub trader: String,
+    pub offer_coin: Coin,
+    pub ask_denom: String,
+}
+/// /terra.market.v1beta1.MsgSwapSend
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct MsgSwapSend {
+    pub from_address: String,
+    pub to_address: String,
+    pub offer_coin: Coin,
+    pub ask_denom: String,
+}
+#[derive(Deserialize, Clone, Serialize, Debug)]
+pub struct Fee {
+    pub amount: Vec<Coin>,
+    #[serde(with = "terra_u64_format", alias = "gas_limit")]
     pub gas: u64,
 }
 
@@ -135,3 +347,63 @@ pub struct NewBlockValidatorUpdate {
     #[serde(with = "terra_u64_format")]
     pub power: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn attributes(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn contract_attributes_split_by_contract() {
+        let split = contract_attributes(&attributes(&[
+            ("contract_address", "terra1a"),
+            ("action", "swap"),
+            ("amount", "10"),
+            ("_contract_address", "terra1b"),
+            ("action", "transfer"),
+        ]));
+        assert_eq!(
+            split,
+            vec![
+                (
+                    "terra1a".to_string(),
+                    attributes(&[("action", "swap"), ("amount", "10")])
+                ),
+                ("terra1b".to_string(), attributes(&[("action", "transfer")])),
+            ]
+        );
+    }
+
+    #[test]
+    fn contract_attributes_before_any_contract() {
+        let split = contract_attributes(&attributes(&[
+            ("action", "orphan"),
+            ("contract_address", "terra1a"),
+        ]));
+        assert_eq!(split, vec![("terra1a".to_string(), vec![])]);
+        assert!(contract_attributes(&[]).is_empty());
+    }
+
+    #[test]
+    fn decode_contract_msg_base64() {
+        let msg = json!({"transfer": {"amount": "10"}});
+        let encoded = Value::String(base64::encode(msg.to_string()));
+        assert_eq!(decode_contract_msg(encoded), msg);
+    }
+
+    #[test]
+    fn decode_contract_msg_passthrough() {
+        let msg = json!({"transfer": {"amount": "10"}});
+        assert_eq!(decode_contract_msg(msg.clone()), msg);
+        // not base64 json, so left as it was
+        let text = Value::String("not base64!".into());
+        assert_eq!(decode_contract_msg(text.clone()), text);
+    }
+}
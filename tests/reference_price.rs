@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_broker::{Broker, BrokerSubscribe, SystemBroker};
+use chrono::Utc;
+use constellation_observer::actor::{
+    ReferencePriceActor, ReferencePriceMapping, ReferencePriceSource,
+};
+use constellation_observer::messages::{
+    MessageBlockEventExchangeRate, MessageReferencePriceConverged, MessageReferencePriceDivergence,
+};
+use constellation_observer::BrokerType;
+use rust_decimal_macros::dec;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// serves `body` as JSON to every request, returning the url to fetch it from
+async fn mock_feed(body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/prices", listener.local_addr().unwrap());
+    actix_rt::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    url
+}
+
+fn source(url: String, denom: &str, pointer: &str, max_age: u64) -> ReferencePriceSource {
+    let mut denoms = HashMap::new();
+    denoms.insert(
+        denom.to_string(),
+        ReferencePriceMapping {
+            pointer: pointer.into(),
+            invert: false,
+        },
+    );
+    ReferencePriceSource {
+        url,
+        interval: 60,
+        max_age,
+        threshold: dec!(0.05),
+        denoms,
+    }
+}
+
+/// writes down the alerts for a denom, in order
+struct Recorder {
+    denom: String,
+    seen: Vec<String>,
+}
+impl Actor for Recorder {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageReferencePriceDivergence>(ctx);
+        self.subscribe_sync::<BrokerType, MessageReferencePriceConverged>(ctx);
+    }
+}
+impl Handler<MessageReferencePriceDivergence> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageReferencePriceDivergence, _ctx: &mut Self::Context) {
+        if msg.denom == self.denom {
+            self.seen.push(format!(
+                "diverged {} {} {}",
+                msg.height, msg.exchange_rate, msg.reference_rate
+            ));
+        }
+    }
+}
+impl Handler<MessageReferencePriceConverged> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageReferencePriceConverged, _ctx: &mut Self::Context) {
+        if msg.denom == self.denom {
+            self.seen.push(format!(
+                "converged {} {} {}",
+                msg.height, msg.exchange_rate, msg.reference_rate
+            ));
+        }
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<String>")]
+struct GetSeen;
+impl Handler<GetSeen> for Recorder {
+    type Result = MessageResult<GetSeen>;
+
+    fn handle(&mut self, _msg: GetSeen, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.seen.clone())
+    }
+}
+
+fn recorder(denom: &str) -> Addr<Recorder> {
+    Recorder {
+        denom: denom.into(),
+        seen: vec![],
+    }
+    .start()
+}
+
+fn rate(height: u64, denom: &str, exchange_rate: rust_decimal::Decimal) {
+    Broker::<SystemBroker>::issue_async(MessageBlockEventExchangeRate {
+        height,
+        time: Utc::now(),
+        denom: denom.into(),
+        exchange_rate,
+    });
+}
+
+/// polls the recorder until it has seen `count` alerts, or gives up
+async fn wait_for(recorder: &Addr<Recorder>, count: usize) -> Vec<String> {
+    let mut seen = vec![];
+    for _ in 0..100 {
+        seen = recorder.send(GetSeen).await.unwrap();
+        if seen.len() >= count {
+            break;
+        }
+        actix_rt::time::sleep(Duration::from_millis(10)).await;
+    }
+    seen
+}
+
+#[actix_rt::test]
+async fn diverges_and_converges() {
+    let recorder = recorder("ukrw");
+    // subscribed once it has answered
+    recorder.send(GetSeen).await.unwrap();
+
+    let url = mock_feed(r#"{"rates":{"KRW":"1000"}}"#).await;
+    ReferencePriceActor::create(source(url, "ukrw", "/rates/KRW", 60)).start();
+
+    // give the actor time to subscribe and the feed time to answer
+    actix_rt::time::sleep(Duration::from_millis(200)).await;
+    rate(100, "ukrw", dec!(1100));
+    assert_eq!(wait_for(&recorder, 1).await, vec!["diverged 100 1100 1000"]);
+    rate(101, "ukrw", dec!(1010));
+    assert_eq!(
+        wait_for(&recorder, 2).await,
+        vec!["diverged 100 1100 1000", "converged 101 1010 1000"]
+    );
+}
+
+#[actix_rt::test]
+async fn stale_reference_is_ignored() {
+    let recorder = recorder("umnt");
+    recorder.send(GetSeen).await.unwrap();
+
+    let url = mock_feed(r#"{"rates":{"MNT":"3000"}}"#).await;
+    ReferencePriceActor::create(source(url, "umnt", "/rates/MNT", 0)).start();
+
+    // give the feed time to answer, so the price is held but already too old
+    actix_rt::time::sleep(Duration::from_millis(200)).await;
+    rate(100, "umnt", dec!(6000));
+    assert!(wait_for(&recorder, 1).await.is_empty());
+}
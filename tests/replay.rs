@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_broker::BrokerSubscribe;
+use constellation_observer::messages::{
+    MessageBlockEnd, MessageBlockEventExchangeRate, MessageBlockProposed,
+};
+use constellation_observer::{replay, BrokerType, MessageTX};
+
+/// writes down what the intake sent, in order
+#[derive(Default)]
+struct Recorder {
+    seen: Vec<String>,
+}
+impl Actor for Recorder {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        self.subscribe_sync::<BrokerType, MessageBlockProposed>(ctx);
+        self.subscribe_sync::<BrokerType, MessageTX>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEventExchangeRate>(ctx);
+        self.subscribe_sync::<BrokerType, MessageBlockEnd>(ctx);
+    }
+}
+impl Handler<MessageBlockProposed> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockProposed, _ctx: &mut Self::Context) {
+        self.seen.push(format!(
+            "proposed {} {} {:?}",
+            msg.height, msg.proposer_address, msg.operator_address
+        ));
+    }
+}
+impl Handler<MessageTX> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageTX, _ctx: &mut Self::Context) {
+        self.seen.push(format!(
+            "tx {} {} {}",
+            msg.tx.height,
+            msg.tx.txhash,
+            msg.tx.log_events().len()
+        ));
+    }
+}
+impl Handler<MessageBlockEventExchangeRate> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEventExchangeRate, _ctx: &mut Self::Context) {
+        self.seen.push(format!(
+            "rate {} {} {}",
+            msg.height, msg.denom, msg.exchange_rate
+        ));
+    }
+}
+impl Handler<MessageBlockEnd> for Recorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: MessageBlockEnd, _ctx: &mut Self::Context) {
+        self.seen.push(format!("end {}", msg.height));
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "Vec<String>")]
+struct GetSeen;
+impl Handler<GetSeen> for Recorder {
+    type Result = MessageResult<GetSeen>;
+
+    fn handle(&mut self, _msg: GetSeen, _ctx: &mut Self::Context) -> Self::Result {
+        MessageResult(self.seen.clone())
+    }
+}
+
+#[actix_rt::test]
+async fn replay_fixture() {
+    let recorder = Recorder::default().start();
+    // subscribed once it has answered
+    recorder.send(GetSeen).await.unwrap();
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/blocks.jsonl");
+    assert_eq!(replay(path).unwrap(), 2);
+
+    let mut seen = vec![];
+    for _ in 0..50 {
+        seen = recorder.send(GetSeen).await.unwrap();
+        if seen.last().map(|s| s == "end 5000001").unwrap_or(false) {
+            break;
+        }
+        actix_rt::time::sleep(Duration::from_millis(10)).await;
+    }
+    let txhash = "F".repeat(64);
+    assert_eq!(
+        seen,
+        vec![
+            "proposed 5000000 0A1B2C3D4E5F60718293A4B5C6D7E8F901234567 None".to_string(),
+            "end 5000000".to_string(),
+            "proposed 5000001 D3E5B5F3D4D6C3B0A1A2B3C4D5E6F708192A3B4C None".to_string(),
+            format!("tx 5000001 {} 2", txhash),
+            "rate 5000001 uusd 40.5".to_string(),
+            "end 5000001".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn replay_missing_file() {
+    assert!(replay(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/missing.jsonl"
+    ))
+    .is_err());
+}